@@ -0,0 +1,399 @@
+// Module: absences
+use crate::response_types::{Absence, Lesson};
+use crate::subjects::Subject;
+use crate::USER_CONFIG;
+use chrono::{Datelike, Duration, NaiveDate};
+use clap::ValueEnum;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashSet};
+
+// kinds of absence events, used by the --type filter
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AbsenceKind {
+    Absence,
+    Late,
+    Exit,
+}
+
+impl AbsenceKind {
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "ABA0" => Some(AbsenceKind::Absence),
+            "ABR0" | "ABR1" => Some(AbsenceKind::Late),
+            "ABU0" => Some(AbsenceKind::Exit),
+            _ => None,
+        }
+    }
+}
+
+/*
+Readable label of the event codes
+ABA0: absence of the whole day
+ABR0: late entry
+ABR1: short delay, usually within the first minutes of the day
+ABU0: early exit
+*/
+pub fn event_label(code: &str) -> &str {
+    match code {
+        "ABA0" => "Assenza",
+        "ABR0" => "Ritardo",
+        "ABR1" => "Ritardo breve",
+        "ABU0" => "Uscita anticipata",
+        _ => code,
+    }
+}
+
+// readable label of the justification reason codes
+pub fn reason_label(code: &str) -> Option<&'static str> {
+    match code {
+        "A" => Some("Motivi di salute"),
+        "AC" => Some("Motivi di salute con certificato medico"),
+        "B" => Some("Motivi di famiglia"),
+        "C" => Some("Problemi di trasporto"),
+        "D" => Some("Attività sportiva, culturale o religiosa"),
+        "E" => Some("Sciopero o assemblea"),
+        _ => None,
+    }
+}
+
+// hour positions listed by the server for an absence
+fn listed_positions(absence: &Absence) -> Vec<u32> {
+    absence
+        .hoursAbsence
+        .iter()
+        .filter_map(|x| match x {
+            Value::Number(v) => v.as_u64().map(|v| v as u32),
+            Value::String(v) => v.parse().ok(),
+            _ => None,
+        })
+        .collect()
+}
+
+/*
+Check whether an hour of a day has been missed because of an absence
+the hours listed by the server are used when present, otherwise
+ABA0: the whole day is missed
+ABR0: late entry, the hours before the hour of entry are missed
+ABR1: short delay, no hour is missed
+ABU0: early exit, the hours from the hour of exit are missed
+*/
+pub fn is_hour_missed(absence: &Absence, position: u32) -> bool {
+    let listed = listed_positions(absence);
+    if !listed.is_empty() {
+        return listed.contains(&position);
+    }
+
+    match (&absence.evtCode[..], absence.evtHPos) {
+        ("ABA0", _) => true,
+        ("ABR0", Some(entry)) => position < entry,
+        ("ABU0", Some(exit)) => position >= exit,
+        _ => false,
+    }
+}
+
+// hours of a lesson missed because of an absence
+pub fn missed_lesson_hours(absence: &Absence, lesson: &Lesson) -> u32 {
+    if absence.evtDate != lesson.evtDate {
+        return 0;
+    }
+
+    let lesson_end = lesson.evtHPos + lesson.evtDuration.max(1);
+    (lesson.evtHPos..lesson_end)
+        .filter(|position| is_hour_missed(absence, *position))
+        .count() as u32
+}
+
+// check whether a lesson has been missed, even partially, because of an absence
+pub fn is_missed(absence: &Absence, lesson: &Lesson) -> bool {
+    missed_lesson_hours(absence, lesson) > 0
+}
+
+// hours of lesson of a day following the daily-hours of config.toml
+pub fn daily_hours(date: &str) -> u32 {
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").expect("Invalid date format");
+    weekday_hours(date)
+}
+
+pub fn weekday_hours(date: NaiveDate) -> u32 {
+    let weekday = date.weekday().num_days_from_monday() as usize;
+    USER_CONFIG
+        .attendance_settings
+        .daily_hours
+        .get(weekday)
+        .cloned()
+        .unwrap_or(0)
+}
+
+/*
+Hours of lesson missed because of an absence
+the hours listed by the server are used when present, otherwise
+the hour of entry or exit is compared with the daily timetable of config.toml
+*/
+pub fn missed_hours(absence: &Absence) -> u32 {
+    // the same positions as is_hour_missed, so that the hours and the lessons agree
+    let listed = listed_positions(absence);
+    if !listed.is_empty() {
+        return listed.len() as u32;
+    }
+
+    let hours_of_day = daily_hours(&absence.evtDate);
+    match (&absence.evtCode[..], absence.evtHPos, absence.evtValue) {
+        ("ABA0", _, _) => hours_of_day,
+        // the hours before the entry
+        ("ABR0", Some(position), _) => position.saturating_sub(1),
+        // the hours from the exit to the end of the day
+        ("ABU0", Some(position), _) => (hours_of_day + 1).saturating_sub(position),
+        ("ABR0", None, Some(value)) | ("ABU0", None, Some(value)) => value,
+        _ => 0,
+    }
+}
+
+pub struct AbsenceSummary {
+    pub full_days: usize,
+    pub late_entries: usize,
+    pub short_delays: usize,
+    pub early_exits: usize,
+    pub missed_hours: u32,
+}
+
+impl AbsenceSummary {
+    pub fn from_absences(absences: &[Absence]) -> Self {
+        let count = |code: &str| absences.iter().filter(|x| x.evtCode == code).count();
+
+        AbsenceSummary {
+            full_days: count("ABA0"),
+            late_entries: count("ABR0"),
+            short_delays: count("ABR1"),
+            early_exits: count("ABU0"),
+            missed_hours: absences.iter().map(missed_hours).sum(),
+        }
+    }
+
+    // share of the annual hours missed, in percentage
+    pub fn missed_share(&self) -> f64 {
+        let annual_hours = USER_CONFIG.attendance_settings.annual_hours;
+        self.missed_hours as f64 / annual_hours as f64 * 100.0
+    }
+
+    // hours that can still be missed before reaching the limit
+    pub fn remaining_hours(&self) -> i64 {
+        let attendance_settings = &USER_CONFIG.attendance_settings;
+        let allowed = (attendance_settings.annual_hours as f64 * attendance_settings.limit / 100.0)
+            .floor() as i64;
+        allowed - self.missed_hours as i64
+    }
+}
+
+pub struct SubjectAttendance {
+    pub subject: String,
+    pub missed_hours: u32,
+    pub held_hours: u32,
+}
+
+impl SubjectAttendance {
+    // share of the hours of the subject that have been missed, in percentage
+    pub fn missed_share(&self) -> f64 {
+        if self.held_hours == 0 {
+            return 0.0;
+        }
+        self.missed_hours as f64 / self.held_hours as f64 * 100.0
+    }
+}
+
+/*
+Hours held and missed for each subject, by matching the absences with the lessons of the same day
+the lessons recorded twice for the same hour(e.g. by two teachers) are counted once
+*/
+pub fn missed_by_subject(absences: &[Absence], lessons: &[Lesson]) -> Vec<SubjectAttendance> {
+    let mut counted: HashSet<(String, u32, String)> = HashSet::new();
+    let mut by_subject: BTreeMap<String, (u32, u32)> = BTreeMap::new();
+
+    for lesson in lessons {
+        let subject = match Subject::from_lesson(lesson) {
+            Some(v) => v.name(),
+            None => continue,
+        };
+
+        let lesson_end = lesson.evtHPos + lesson.evtDuration.max(1);
+        for position in lesson.evtHPos..lesson_end {
+            if !counted.insert((lesson.evtDate.clone(), position, subject.clone())) {
+                continue;
+            }
+
+            let missed = absences
+                .iter()
+                .filter(|x| x.evtDate == lesson.evtDate)
+                .any(|x| is_hour_missed(x, position));
+
+            let entry = by_subject.entry(subject.clone()).or_insert((0, 0));
+            entry.1 += 1;
+            if missed {
+                entry.0 += 1;
+            }
+        }
+    }
+
+    let mut result: Vec<SubjectAttendance> = by_subject
+        .into_iter()
+        .map(|(subject, (missed_hours, held_hours))| SubjectAttendance {
+            subject,
+            missed_hours,
+            held_hours,
+        })
+        .collect();
+    result.sort_by_key(|x| std::cmp::Reverse(x.missed_hours));
+
+    result
+}
+
+// days after an absence period whose agenda is shown by the catchup command
+pub const CATCHUP_DAYS: i64 = 7;
+
+/*
+Group the days with missed hours in periods, both ends included
+two days belong to the same period if there are no lessons between them, e.g. over the weekend
+*/
+pub fn absence_periods(absences: &[Absence]) -> Vec<(NaiveDate, NaiveDate)> {
+    let mut dates: Vec<NaiveDate> = absences
+        .iter()
+        .filter(|x| missed_hours(x) > 0)
+        .filter_map(|x| NaiveDate::parse_from_str(&x.evtDate, "%Y-%m-%d").ok())
+        .collect();
+    dates.sort();
+    dates.dedup();
+
+    let mut periods: Vec<(NaiveDate, NaiveDate)> = Vec::new();
+    for date in dates {
+        if let Some(last) = periods.last_mut() {
+            let mut day = last.1 + Duration::days(1);
+            while day < date && weekday_hours(day) == 0 {
+                day += Duration::days(1);
+            }
+            if day == date {
+                last.1 = date;
+                continue;
+            }
+        }
+        periods.push((date, date));
+    }
+
+    periods
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::response_types::fixtures::{absence, lesson};
+    use serde_json::json;
+
+    // 2025-10-06 is a monday, config.example.toml has 6 hours from monday to friday
+
+    #[test]
+    fn missed_hours_of_the_listed_positions() {
+        let listed = absence("ABR0", "2025-10-06", Some(4), json!([1, "2", "3"]));
+        assert_eq!(missed_hours(&listed), 3);
+        // values that are not positions are ignored, as in is_hour_missed
+        let listed = absence("ABA0", "2025-10-06", None, json!([1, null]));
+        assert_eq!(missed_hours(&listed), 1);
+        assert!(is_hour_missed(&listed, 1));
+        assert!(!is_hour_missed(&listed, 2));
+    }
+
+    #[test]
+    fn missed_hours_of_a_whole_day() {
+        assert_eq!(
+            missed_hours(&absence("ABA0", "2025-10-06", None, json!([]))),
+            6
+        );
+        // saturday has no lessons
+        assert_eq!(
+            missed_hours(&absence("ABA0", "2025-10-11", None, json!([]))),
+            0
+        );
+    }
+
+    #[test]
+    fn missed_hours_of_entries_and_exits() {
+        assert_eq!(
+            missed_hours(&absence("ABR0", "2025-10-06", Some(3), json!([]))),
+            2
+        );
+        assert_eq!(
+            missed_hours(&absence("ABU0", "2025-10-06", Some(5), json!([]))),
+            2
+        );
+        assert_eq!(
+            missed_hours(&absence("ABR1", "2025-10-06", Some(1), json!([]))),
+            0
+        );
+
+        let mut late = absence("ABR0", "2025-10-06", None, json!([]));
+        late.evtValue = Some(1);
+        assert_eq!(missed_hours(&late), 1);
+    }
+
+    #[test]
+    fn absence_codes_are_decoded() {
+        assert_eq!(AbsenceKind::from_code("ABR1"), Some(AbsenceKind::Late));
+        assert_eq!(AbsenceKind::from_code("ABU0"), Some(AbsenceKind::Exit));
+        assert_eq!(AbsenceKind::from_code("XYZ"), None);
+        assert_eq!(event_label("ABR1"), "Ritardo breve");
+        // unknown codes are shown as sent
+        assert_eq!(event_label("XYZ"), "XYZ");
+        assert_eq!(
+            reason_label("AC"),
+            Some("Motivi di salute con certificato medico")
+        );
+        assert_eq!(reason_label("Z"), None);
+    }
+
+    #[test]
+    fn missed_by_subject_matches_the_hours_of_the_lessons() {
+        let mut physics = lesson("2025-10-06", 1, Some((2, "FISICA")), "Moto");
+        physics.evtDuration = 2;
+        // the same hour recorded by a second teacher
+        let mut copresence = lesson("2025-10-06", 2, Some((2, "FISICA")), "Moto");
+        copresence.authorName = "BIANCHI ANNA".to_string();
+        let lessons = vec![
+            physics,
+            copresence,
+            lesson("2025-10-06", 3, Some((1, "MATEMATICA")), "Derivate"),
+            lesson("2025-10-07", 1, Some((1, "MATEMATICA")), "Integrali"),
+            lesson("2025-10-07", 5, Some((2, "FISICA")), "Forze"),
+            lesson("2025-10-08", 1, None, "Assemblea"),
+        ];
+        let absences = vec![
+            // entry at the third hour, the first two are missed
+            absence("ABR0", "2025-10-06", Some(3), json!([])),
+            // exit at the fifth hour
+            absence("ABU0", "2025-10-07", Some(5), json!([])),
+        ];
+
+        let attendance = missed_by_subject(&absences, &lessons);
+        let rows: Vec<(&str, u32, u32)> = attendance
+            .iter()
+            .map(|x| (x.subject.as_str(), x.missed_hours, x.held_hours))
+            .collect();
+        assert_eq!(rows, vec![("FISICA", 3, 3), ("MATEMATICA", 0, 2)]);
+        assert_eq!(attendance[0].missed_share(), 100.0);
+        assert_eq!(attendance[1].missed_share(), 0.0);
+    }
+
+    #[test]
+    fn absence_periods_bridge_the_days_without_lessons() {
+        let absences = vec![
+            absence("ABA0", "2025-10-13", None, json!([])),
+            // friday and monday, the weekend has no lessons
+            absence("ABA0", "2025-10-10", None, json!([])),
+            // a short delay misses no hour
+            absence("ABR1", "2025-10-15", Some(1), json!([])),
+            absence("ABA0", "2025-10-16", None, json!([])),
+            absence("ABU0", "2025-10-17", Some(4), json!([])),
+        ];
+        let date = |day| NaiveDate::from_ymd_opt(2025, 10, day).unwrap();
+        assert_eq!(
+            absence_periods(&absences),
+            vec![(date(10), date(13)), (date(16), date(17))]
+        );
+    }
+}
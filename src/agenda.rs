@@ -0,0 +1,79 @@
+// Module: agenda
+use crate::assessments::is_assessment;
+use crate::response_types::Agenda;
+use clap::ValueEnum;
+
+// kinds of agenda events, used by the --type filter
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AgendaKind {
+    Homework,
+    Note,
+    Test,
+    Event,
+}
+
+impl AgendaKind {
+    // tests have no code of their own, they are announced in the notes
+    pub fn from_agenda(agenda: &Agenda) -> Self {
+        match &agenda.evtCode[..] {
+            "AGHW" => AgendaKind::Homework,
+            _ if is_assessment(&agenda.notes) => AgendaKind::Test,
+            "AGNT" => AgendaKind::Note,
+            _ => AgendaKind::Event,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            AgendaKind::Homework => "Compiti",
+            AgendaKind::Note => "Annotazione",
+            AgendaKind::Test => "Verifica",
+            AgendaKind::Event => "Evento",
+        }
+    }
+}
+
+/*
+Readable label of the event codes
+AGHW: homework
+AGNT: note of the teacher
+AGCR: reservation of a room or of a meeting
+*/
+pub fn event_label(code: &str) -> &str {
+    match code {
+        "AGHW" => "Compiti",
+        "AGNT" => "Annotazione",
+        "AGCR" => "Prenotazione",
+        _ => code,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::response_types::fixtures::agenda;
+
+    const BEGIN: &str = "2025-10-06T08:00:00+02:00";
+
+    #[test]
+    fn kinds_of_the_agenda_events() {
+        let kind = |code, notes| AgendaKind::from_agenda(&agenda(1, code, BEGIN, notes));
+        // homework about a test is still homework
+        assert_eq!(
+            kind("AGHW", "Ripassare per la verifica"),
+            AgendaKind::Homework
+        );
+        assert_eq!(kind("AGNT", "Verifica di fisica"), AgendaKind::Test);
+        assert_eq!(kind("AGCR", "Interrogazione"), AgendaKind::Test);
+        assert_eq!(kind("AGNT", "Uscita didattica"), AgendaKind::Note);
+        assert_eq!(kind("AGCR", "Colloquio"), AgendaKind::Event);
+    }
+
+    #[test]
+    fn labels_of_the_agenda_codes() {
+        assert_eq!(event_label("AGCR"), "Prenotazione");
+        // unknown codes are shown as sent
+        assert_eq!(event_label("AGXX"), "AGXX");
+        assert_eq!(AgendaKind::Test.label(), "Verifica");
+    }
+}
@@ -1,13 +1,19 @@
 // Module: api
+use crate::calendar::SchoolCalendar;
 use crate::response_types::*;
 use crate::{TOKEN, USER_CONFIG};
-use chrono::{offset::Local, Datelike, Duration, Weekday};
+use chrono::{offset::Local, Datelike, Duration, NaiveDate, Weekday};
+use reqwest::{Method, StatusCode};
 use std::io::Write;
 use std::path::Path;
 
 use serde::{Deserialize, Serialize};
 
 const BASE_URL: &str = "https://web.spaggiari.eu/rest/v1";
+// downloads and the school calendar give up after this many re-logins
+const MAX_RELOGINS: u8 = 2;
+const CARD_FILE: &str = "card.json";
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Credentials {
     pub username: String,
@@ -99,6 +105,22 @@ fn get_current_lessons_week_date() -> (String, String) {
     (monday_iso, friday_iso)
 }
 
+// the school year starts on the first of september
+pub fn get_school_year_start() -> String {
+    let today = Local::now().date_naive();
+    let year = if today.month() >= 9 {
+        today.year()
+    } else {
+        today.year() - 1
+    };
+
+    format!("{}0901", year)
+}
+
+pub fn get_today() -> String {
+    Local::now().format("%Y%m%d").to_string()
+}
+
 fn get_current_agenda_week_date() -> (String, String) {
     let mut current_time = Local::now();
 
@@ -126,8 +148,30 @@ fn get_current_agenda_week_date() -> (String, String) {
     (current_day_iso, friday_iso)
 }
 
-async fn get_request(url: &str) -> String {
-    let mut token_credential = match TOKEN.lock().unwrap().as_ref() {
+/*
+Narrow a week(YYYYMMDD) to its school days following the school calendar
+when shift is true a week without school days, e.g. during the holidays, moves to the next one with lessons
+*/
+async fn school_week((start, end): (String, String), shift: bool) -> (String, String) {
+    let calendar = school_calendar(false).await;
+    let start = NaiveDate::parse_from_str(&start, "%Y%m%d").expect("Invalid date format");
+    let end = NaiveDate::parse_from_str(&end, "%Y%m%d").expect("Invalid date format");
+
+    let (start, end) = if shift {
+        calendar.next_school_range(start, end)
+    } else {
+        calendar.trim(start, end).unwrap_or((start, end))
+    };
+
+    (
+        start.format("%Y%m%d").to_string(),
+        end.format("%Y%m%d").to_string(),
+    )
+}
+
+// token of the current session, logging in if there is none
+async fn session_token() -> TokenCredential {
+    let token_credential = match TOKEN.lock().unwrap().as_ref() {
         Some(v) => v.clone(),
         None => TokenCredential {
             token: String::new(),
@@ -137,23 +181,54 @@ async fn get_request(url: &str) -> String {
     };
 
     // if token is not present, login
-    if let Some(v) = foreplay().await {
-        token_credential = v;
+    match foreplay().await {
+        Some(v) => v,
+        None => token_credential,
     }
+}
+
+async fn get_request(url: &str) -> String {
+    match try_get_request(url).await {
+        Ok(v) => v,
+        Err(e) => panic!("error sending get request at {}: {}", url, e),
+    }
+}
+
+// the get request of the endpoints that can fail without stopping the command
+async fn try_get_request(url: &str) -> Result<String, reqwest::Error> {
+    let token_credential = session_token().await;
 
     // process url with studentId
     let url = process_url(url.to_owned(), &token_credential.studentId);
 
     let client = reqwest::Client::new();
-    let raw_result = match client
+    client
         .get(&url)
         .headers(USER_CONFIG.default_headers.to_owned())
         .header("z-auth-token", token_credential.token.as_str())
         .send()
+        .await?
+        .text()
+        .await
+}
+
+// some endpoints, e.g. the ones marking something as read, only accept POST requests
+async fn post_request(url: &str) -> String {
+    let token_credential = session_token().await;
+
+    // process url with studentId
+    let url = process_url(url.to_owned(), &token_credential.studentId);
+
+    let client = reqwest::Client::new();
+    let raw_result = match client
+        .post(&url)
+        .headers(USER_CONFIG.default_headers.to_owned())
+        .header("z-auth-token", token_credential.token.as_str())
+        .send()
         .await
     {
         Ok(v) => v,
-        Err(e) => panic!("error sending get request at {}: {}", url, e),
+        Err(e) => panic!("error sending post request at {}: {}", url, e),
     };
 
     raw_result
@@ -162,6 +237,60 @@ async fn get_request(url: &str) -> String {
         .expect("Error at converting response to text")
 }
 
+/*
+Download a file, e.g. an attachment, some files are only sent to POST requests
+the server answers with 401 instead of the file when the token is expired,
+any other error status is permanent
+*/
+async fn download_request(url: &str, method: Method) -> Vec<u8> {
+    let mut relogins = 0;
+    loop {
+        let token_credential = session_token().await;
+
+        // process url with studentId
+        let processed_url = process_url(url.to_owned(), &token_credential.studentId);
+
+        let client = reqwest::Client::new();
+        let raw_result = match client
+            .request(method.clone(), &processed_url)
+            .headers(USER_CONFIG.default_headers.to_owned())
+            .header("z-auth-token", token_credential.token.as_str())
+            .send()
+            .await
+        {
+            Ok(v) => v,
+            Err(e) => panic!(
+                "error sending {} request at {}: {}",
+                method, processed_url, e
+            ),
+        };
+
+        let status = raw_result.status();
+        if status == StatusCode::UNAUTHORIZED && relogins < MAX_RELOGINS {
+            relogins += 1;
+            println!("Re-login...");
+            // Re-login
+            let token_credential = login().await;
+
+            // replace the token
+            TOKEN.lock().unwrap().replace(token_credential);
+            continue;
+        }
+        if !status.is_success() {
+            panic!(
+                "[ERROR]: download at {} failed with {}",
+                processed_url, status
+            );
+        }
+
+        let bytes = raw_result
+            .bytes()
+            .await
+            .expect("Error at reading the downloaded file");
+        return bytes.to_vec();
+    }
+}
+
 fn process_url(url: String, student_id: &str) -> String {
     url.replace("<studentID>", student_id)
 }
@@ -312,17 +441,31 @@ pub async fn grades_request() -> Grades {
 
 // The default behavior of the request is fetching the agenda of the current week
 pub async fn agenda_request(selected_date: Option<String>) -> Agendas {
-    let (start, end): (String, String) = if selected_date.is_none() {
-        get_current_agenda_week_date()
-    } else {
-        let date = selected_date.unwrap();
-        if date == "nextweek" {
-            get_next_week_date()
-        } else {
-            (date.clone(), date)
+    let (start, end): (String, String) = match selected_date {
+        None => school_week(get_current_agenda_week_date(), true).await,
+        Some(date) => {
+            if date == "nextweek" {
+                school_week(get_next_week_date(), true).await
+            } else {
+                (date.clone(), date)
+            }
         }
     };
 
+    agenda_range_request(&start, &end).await
+}
+
+// fetch the agenda between two dates(YYYYMMDD), both included
+pub async fn agenda_range_request(start: &str, end: &str) -> Agendas {
+    let mut result = Agendas::new();
+    for (chunk_start, chunk_end) in split_date_range(start, end) {
+        let mut agenda = agenda_chunk_request(&chunk_start, &chunk_end).await;
+        result.agenda.append(&mut agenda.agenda);
+    }
+    result
+}
+
+async fn agenda_chunk_request(start: &str, end: &str) -> Agendas {
     // make the url
     let url = format!(
         "{}/students/<studentID>/agenda/all/{}/{}",
@@ -365,14 +508,45 @@ pub async fn agenda_request(selected_date: Option<String>) -> Agendas {
 
 pub async fn lessons_request(selected_date: Option<String>) -> Lessons {
     // TODO: add shortcuts for displaying agenda of the next day, the previous day and so on
-    let (start, end): (String, String) = if selected_date.is_none() {
-        get_current_lessons_week_date()
-    } else {
+    let (start, end): (String, String) = match selected_date {
+        None => school_week(get_current_lessons_week_date(), false).await,
         // TODO: add shortcuts for displaying agenda of the next day, the previous day and so on
-        let date = selected_date.unwrap();
-        (date.clone(), date)
+        Some(date) => (date.clone(), date),
     };
 
+    lessons_range_request(&start, &end).await
+}
+
+// split a range of dates(YYYYMMDD) in chunks of at most 30 days, so that long ranges can be requested
+fn split_date_range(start: &str, end: &str) -> Vec<(String, String)> {
+    let start = NaiveDate::parse_from_str(start, "%Y%m%d").expect("Invalid date format");
+    let end = NaiveDate::parse_from_str(end, "%Y%m%d").expect("Invalid date format");
+
+    let mut chunks: Vec<(String, String)> = Vec::new();
+    let mut chunk_start = start;
+    while chunk_start <= end {
+        let chunk_end = std::cmp::min(chunk_start + Duration::days(29), end);
+        chunks.push((
+            chunk_start.format("%Y%m%d").to_string(),
+            chunk_end.format("%Y%m%d").to_string(),
+        ));
+        chunk_start = chunk_end + Duration::days(1);
+    }
+
+    chunks
+}
+
+// fetch the lessons between two dates(YYYYMMDD), both included
+pub async fn lessons_range_request(start: &str, end: &str) -> Lessons {
+    let mut result = Lessons::new();
+    for (chunk_start, chunk_end) in split_date_range(start, end) {
+        let mut lessons = lessons_chunk_request(&chunk_start, &chunk_end).await;
+        result.lessons.append(&mut lessons.lessons);
+    }
+    result
+}
+
+async fn lessons_chunk_request(start: &str, end: &str) -> Lessons {
     // make the url
     let url = format!(
         "{}/students/<studentID>/lessons/{}/{}",
@@ -413,3 +587,423 @@ pub async fn lessons_request(selected_date: Option<String>) -> Lessons {
     }
     result
 }
+
+pub async fn notes_request() -> Notes {
+    let url = format!("{}/students/<studentID>/notes/all", BASE_URL);
+    // disable the warning for unused_assignments
+    #[allow(unused_assignments)]
+    let mut result = Notes::new();
+    loop {
+        let raw_result = get_request(&url).await;
+
+        match serde_json::from_str(&raw_result) {
+            Ok(response) => {
+                match response {
+                    ResponseResult::ExpiredToken(_) => {
+                        println!("Re-login...");
+                        // Re-login
+                        let token_credential = login().await;
+
+                        // replace the token
+                        TOKEN.lock().unwrap().replace(token_credential);
+                    }
+                    ResponseResult::Notes(payload) => {
+                        result = payload;
+                        break;
+                    }
+                    _ => {
+                        panic!("[ERROR]: Wrong return type upon api call {}", raw_result)
+                    }
+                }
+            }
+            Err(e) => {
+                panic!("[ERROR]: Parsing notes response: {}", e)
+            }
+        };
+    }
+    result
+}
+
+// read a note, the server marks it as read and sends its whole text
+pub async fn read_note_request(code: &str, evt_id: u32) -> NoteEvent {
+    let url = format!(
+        "{}/students/<studentID>/notes/{}/read/{}",
+        BASE_URL, code, evt_id
+    );
+
+    loop {
+        let raw_result = post_request(&url).await;
+
+        match serde_json::from_str(&raw_result) {
+            Ok(response) => match response {
+                ResponseResult::ExpiredToken(_) => {
+                    println!("Re-login...");
+                    // Re-login
+                    let token_credential = login().await;
+
+                    // replace the token
+                    TOKEN.lock().unwrap().replace(token_credential);
+                }
+                ResponseResult::NoteRead(payload) => return payload.event,
+                _ => {
+                    panic!("[ERROR]: Wrong return type upon api call {}", raw_result)
+                }
+            },
+            Err(e) => {
+                panic!("[ERROR]: Parsing note response: {}", e)
+            }
+        };
+    }
+}
+
+pub async fn noticeboard_request() -> Noticeboard {
+    let url = format!("{}/students/<studentID>/noticeboard", BASE_URL);
+    // disable the warning for unused_assignments
+    #[allow(unused_assignments)]
+    let mut result = Noticeboard::new();
+    loop {
+        let raw_result = get_request(&url).await;
+
+        match serde_json::from_str(&raw_result) {
+            Ok(response) => {
+                match response {
+                    ResponseResult::ExpiredToken(_) => {
+                        println!("Re-login...");
+                        // Re-login
+                        let token_credential = login().await;
+
+                        // replace the token
+                        TOKEN.lock().unwrap().replace(token_credential);
+                    }
+                    ResponseResult::Noticeboard(payload) => {
+                        result = payload;
+                        break;
+                    }
+                    _ => {
+                        panic!("[ERROR]: Wrong return type upon api call {}", raw_result)
+                    }
+                }
+            }
+            Err(e) => {
+                panic!("[ERROR]: Parsing noticeboard response: {}", e)
+            }
+        };
+    }
+    result
+}
+
+// read a communication of the noticeboard, the server marks it as read
+pub async fn read_notice_request(notice: &Notice) -> NoticeContent {
+    let url = format!(
+        "{}/students/<studentID>/noticeboard/read/{}/{}/101",
+        BASE_URL, notice.evtCode, notice.pubId
+    );
+
+    loop {
+        let raw_result = post_request(&url).await;
+
+        match serde_json::from_str(&raw_result) {
+            Ok(response) => match response {
+                ResponseResult::ExpiredToken(_) => {
+                    println!("Re-login...");
+                    // Re-login
+                    let token_credential = login().await;
+
+                    // replace the token
+                    TOKEN.lock().unwrap().replace(token_credential);
+                }
+                ResponseResult::NoticeRead(payload) => return payload.item,
+                _ => {
+                    panic!("[ERROR]: Wrong return type upon api call {}", raw_result)
+                }
+            },
+            Err(e) => {
+                panic!("[ERROR]: Parsing noticeboard response: {}", e)
+            }
+        };
+    }
+}
+
+pub async fn notice_attachment_request(notice: &Notice, attachment: &NoticeAttachment) -> Vec<u8> {
+    let url = format!(
+        "{}/students/<studentID>/noticeboard/attach/{}/{}/{}",
+        BASE_URL, notice.evtCode, notice.pubId, attachment.attachNum
+    );
+
+    download_request(&url, Method::GET).await
+}
+
+pub async fn didactics_request() -> Didactics {
+    let url = format!("{}/students/<studentID>/didactics", BASE_URL);
+    // disable the warning for unused_assignments
+    #[allow(unused_assignments)]
+    let mut result = Didactics::new();
+    loop {
+        let raw_result = get_request(&url).await;
+
+        match serde_json::from_str(&raw_result) {
+            Ok(response) => {
+                match response {
+                    ResponseResult::ExpiredToken(_) => {
+                        println!("Re-login...");
+                        // Re-login
+                        let token_credential = login().await;
+
+                        // replace the token
+                        TOKEN.lock().unwrap().replace(token_credential);
+                    }
+                    ResponseResult::Didactics(payload) => {
+                        result = payload;
+                        break;
+                    }
+                    _ => {
+                        panic!("[ERROR]: Wrong return type upon api call {}", raw_result)
+                    }
+                }
+            }
+            Err(e) => {
+                panic!("[ERROR]: Parsing didactics response: {}", e)
+            }
+        };
+    }
+    result
+}
+
+// a file of the didactic materials, or the JSON describing a link or a text
+pub async fn didactic_item_request(content_id: u32) -> Vec<u8> {
+    let url = format!(
+        "{}/students/<studentID>/didactics/item/{}",
+        BASE_URL, content_id
+    );
+
+    download_request(&url, Method::GET).await
+}
+
+// the endpoints of the documents only accept POST requests
+pub async fn documents_request() -> Documents {
+    let url = format!("{}/students/<studentID>/documents", BASE_URL);
+    // disable the warning for unused_assignments
+    #[allow(unused_assignments)]
+    let mut result = Documents::new();
+    loop {
+        let raw_result = post_request(&url).await;
+
+        match serde_json::from_str(&raw_result) {
+            Ok(response) => {
+                match response {
+                    ResponseResult::ExpiredToken(_) => {
+                        println!("Re-login...");
+                        // Re-login
+                        let token_credential = login().await;
+
+                        // replace the token
+                        TOKEN.lock().unwrap().replace(token_credential);
+                    }
+                    ResponseResult::Documents(payload) => {
+                        result = payload;
+                        break;
+                    }
+                    _ => {
+                        panic!("[ERROR]: Wrong return type upon api call {}", raw_result)
+                    }
+                }
+            }
+            Err(e) => {
+                panic!("[ERROR]: Parsing documents response: {}", e)
+            }
+        };
+    }
+    result
+}
+
+// check whether a document can be downloaded
+pub async fn document_check_request(hash: &str) -> bool {
+    let url = format!("{}/students/<studentID>/documents/check/{}", BASE_URL, hash);
+
+    loop {
+        let raw_result = post_request(&url).await;
+
+        match serde_json::from_str(&raw_result) {
+            Ok(response) => match response {
+                ResponseResult::ExpiredToken(_) => {
+                    println!("Re-login...");
+                    // Re-login
+                    let token_credential = login().await;
+
+                    // replace the token
+                    TOKEN.lock().unwrap().replace(token_credential);
+                }
+                ResponseResult::DocumentCheck(payload) => return payload.document.available,
+                _ => {
+                    panic!("[ERROR]: Wrong return type upon api call {}", raw_result)
+                }
+            },
+            Err(e) => {
+                panic!("[ERROR]: Parsing document response: {}", e)
+            }
+        };
+    }
+}
+
+pub async fn document_request(hash: &str) -> Vec<u8> {
+    let url = format!("{}/students/<studentID>/documents/read/{}", BASE_URL, hash);
+
+    download_request(&url, Method::POST).await
+}
+
+pub async fn subjects_request() -> Subjects {
+    let url = format!("{}/students/<studentID>/subjects", BASE_URL);
+    // disable the warning for unused_assignments
+    #[allow(unused_assignments)]
+    let mut result = Subjects::new();
+    loop {
+        let raw_result = get_request(&url).await;
+
+        match serde_json::from_str(&raw_result) {
+            Ok(response) => {
+                match response {
+                    ResponseResult::ExpiredToken(_) => {
+                        println!("Re-login...");
+                        // Re-login
+                        let token_credential = login().await;
+
+                        // replace the token
+                        TOKEN.lock().unwrap().replace(token_credential);
+                    }
+                    ResponseResult::Subjects(payload) => {
+                        result = payload;
+                        break;
+                    }
+                    _ => {
+                        panic!("[ERROR]: Wrong return type upon api call {}", raw_result)
+                    }
+                }
+            }
+            Err(e) => {
+                panic!("[ERROR]: Parsing subjects response: {}", e)
+            }
+        };
+    }
+    result
+}
+
+/*
+The calendar of the school year, it is fetched once a year or when updating
+the calendar only refines the school days, so a failed fetch falls back to the daily hours
+a failed fetch is stored as well and tried again the following day
+*/
+pub async fn school_calendar(update: bool) -> SchoolCalendar {
+    let year = get_school_year_start();
+    let today = get_today();
+    let stored = if update { None } else { SchoolCalendar::load() };
+    let stored =
+        stored.filter(|x| x.year == year && x.failed_on.as_ref().is_none_or(|date| *date == today));
+    if let Some(calendar) = stored {
+        return calendar;
+    }
+
+    let calendar = match calendar_request().await {
+        Some(v) => SchoolCalendar::from_calendar(v, &year),
+        None => SchoolCalendar {
+            year,
+            failed_on: Some(today),
+            ..SchoolCalendar::default()
+        },
+    };
+    calendar.store();
+    calendar
+}
+
+// None when the calendar can not be fetched, every error of the server looks like an expired token
+async fn calendar_request() -> Option<Calendar> {
+    let url = format!("{}/students/<studentID>/calendar/all", BASE_URL);
+    let mut relogins = 0;
+    loop {
+        let raw_result = match try_get_request(&url).await {
+            Ok(v) => v,
+            Err(e) => {
+                eprintln!("[WARNING]: Fetching the school calendar: {}", e);
+                return None;
+            }
+        };
+
+        match serde_json::from_str(&raw_result) {
+            Ok(ResponseResult::ExpiredToken(_)) if relogins < MAX_RELOGINS => {
+                relogins += 1;
+                println!("Re-login...");
+                // Re-login
+                let token_credential = login().await;
+
+                // replace the token
+                TOKEN.lock().unwrap().replace(token_credential);
+            }
+            Ok(ResponseResult::Calendar(payload)) => return Some(payload),
+            _ => {
+                eprintln!("[WARNING]: Unexpected school calendar response");
+                return None;
+            }
+        };
+    }
+}
+
+/*
+The profile card, stored in the data directory since it does not change during the year
+update fetches it again
+*/
+pub async fn profile_card(update: bool) -> Card {
+    let path = USER_CONFIG.data_path(CARD_FILE);
+    let stored: Option<Card> = if update {
+        None
+    } else {
+        std::fs::File::open(&path)
+            .ok()
+            .and_then(|file| serde_json::from_reader(file).ok())
+    };
+    if let Some(card) = stored {
+        return card;
+    }
+
+    let card = card_request().await;
+    // written to a temporary file first, so that an interrupted write leaves no broken card
+    let temp_path = path.with_extension("json.tmp");
+    let file = match std::fs::File::create(&temp_path) {
+        Ok(v) => v,
+        Err(e) => panic!("error at creating the card file: {}", e),
+    };
+    match serde_json::to_writer(file, &card) {
+        Ok(_) => (),
+        Err(e) => panic!("error at writing the card file: {}", e),
+    };
+    if let Err(e) = std::fs::rename(&temp_path, &path) {
+        panic!("error at replacing the card file: {}", e);
+    }
+    card
+}
+
+async fn card_request() -> Card {
+    let url = format!("{}/students/<studentID>/card", BASE_URL);
+
+    loop {
+        let raw_result = get_request(&url).await;
+
+        match serde_json::from_str(&raw_result) {
+            Ok(response) => match response {
+                ResponseResult::ExpiredToken(_) => {
+                    println!("Re-login...");
+                    // Re-login
+                    let token_credential = login().await;
+
+                    // replace the token
+                    TOKEN.lock().unwrap().replace(token_credential);
+                }
+                ResponseResult::Card(payload) => return payload.card,
+                _ => {
+                    panic!("[ERROR]: Wrong return type upon api call {}", raw_result)
+                }
+            },
+            Err(e) => {
+                panic!("[ERROR]: Parsing card response: {}", e)
+            }
+        };
+    }
+}
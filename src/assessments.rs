@@ -0,0 +1,220 @@
+// Module: assessments
+use crate::response_types::{Agenda, Lesson};
+use crate::subjects::{agenda_subject, normalize, subjects_by_teacher, Subject};
+use crate::syllabus::{topics_by_subject, Topic};
+use crate::USER_CONFIG;
+use chrono::NaiveDate;
+
+// keep only the words of a text, so that "test" does not match "testo"
+fn words(text: &str) -> String {
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|x| !x.is_empty())
+        .map(|x| x.to_lowercase())
+        .collect();
+    format!(" {} ", words.join(" "))
+}
+
+// check whether a text announces a test, the keywords are set in config.toml
+pub fn is_assessment(text: &str) -> bool {
+    let text = words(text);
+    USER_CONFIG
+        .assessment_settings
+        .keywords
+        .iter()
+        .any(|keyword| text.contains(&words(keyword)))
+}
+
+// a test found in the agenda or in the topics of the lessons
+pub struct Assessment {
+    // evtId of the agenda event, None for the tests found in the lessons
+    pub id: Option<u32>,
+    pub date: NaiveDate,
+    pub subject: Option<Subject>,
+    pub text: String,
+    pub teacher: String,
+}
+
+impl Assessment {
+    pub fn subject_name(&self) -> String {
+        self.subject.as_ref().map(|x| x.name()).unwrap_or_default()
+    }
+}
+
+/*
+Detect the tests announced in the agenda and the ones recorded as topic of a lesson
+the events of the agenda without a subject get the subject taught by their author
+*/
+pub fn detect(agenda: &[Agenda], lessons: &[Lesson]) -> Vec<Assessment> {
+    let teachers = subjects_by_teacher(lessons);
+    let mut assessments: Vec<Assessment> = Vec::new();
+
+    for event in agenda.iter().filter(|x| is_assessment(&x.notes)) {
+        let date = match event.evtDatetimeBegin.get(..10).map(|x| x.parse()) {
+            Some(Ok(v)) => v,
+            _ => continue,
+        };
+        let subject = agenda_subject(event, &teachers);
+        assessments.push(Assessment {
+            id: Some(event.evtId),
+            date,
+            subject,
+            text: event.notes.trim().to_string(),
+            teacher: event.authorName.clone(),
+        });
+    }
+
+    for lesson in lessons.iter().filter(|x| is_assessment(&x.lessonArg)) {
+        let date = match lesson.evtDate.parse() {
+            Ok(v) => v,
+            Err(_) => continue,
+        };
+        let subject = Subject::from_lesson(lesson);
+        // the same test recorded by more than one teacher or both in the agenda and in the lessons
+        if assessments
+            .iter()
+            .any(|x| x.date == date && same_subject(&x.subject, &subject))
+        {
+            continue;
+        }
+        assessments.push(Assessment {
+            id: None,
+            date,
+            subject,
+            text: lesson.lessonArg.trim().to_string(),
+            teacher: lesson.authorName.clone(),
+        });
+    }
+
+    assessments.sort_by_key(|x| x.date);
+    assessments
+}
+
+fn same_subject(a: &Option<Subject>, b: &Option<Subject>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => normalize(&a.desc) == normalize(&b.desc),
+        _ => false,
+    }
+}
+
+/*
+Topics covered in a subject after the previous test, up to the day before the test
+without a previous test every topic of the lessons is returned
+*/
+pub fn topics_since_previous(
+    assessment: &Assessment,
+    assessments: &[Assessment],
+    lessons: &[Lesson],
+) -> Vec<Topic> {
+    let subject = match &assessment.subject {
+        Some(v) => v,
+        None => return Vec::new(),
+    };
+    let previous = assessments
+        .iter()
+        .filter(|x| x.date < assessment.date && same_subject(&x.subject, &assessment.subject))
+        .map(|x| x.date)
+        .max();
+
+    let lessons = lessons
+        .iter()
+        .filter(|x| match x.evtDate.parse::<NaiveDate>() {
+            Ok(date) => previous.is_none_or(|previous| date > previous) && date < assessment.date,
+            Err(_) => false,
+        });
+
+    topics_by_subject(lessons)
+        .remove(&subject.name())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::response_types::fixtures::{agenda, lesson};
+
+    #[test]
+    fn assessments_are_found_by_whole_words() {
+        assert!(is_assessment("Test di inglese"));
+        assert!(is_assessment("VERIFICHE: capitoli 3,4"));
+        assert!(is_assessment("Compito  in\nclasse di latino"));
+        assert!(!is_assessment("Analisi del testo"));
+        assert!(!is_assessment("Compito di casa"));
+        assert!(!is_assessment(""));
+    }
+
+    fn lessons() -> Vec<Lesson> {
+        vec![
+            lesson("2025-10-06", 1, Some((1, "MATEMATICA")), "Limiti"),
+            lesson(
+                "2025-10-08",
+                1,
+                Some((1, "MATEMATICA")),
+                "Verifica sui limiti",
+            ),
+            lesson("2025-10-09", 1, Some((1, "MATEMATICA")), "Derivate"),
+            lesson("2025-10-13", 1, Some((2, "FISICA")), "Moto"),
+            lesson("2025-10-14", 1, Some((1, "MATEMATICA")), "Integrali"),
+            lesson("2025-10-16", 1, Some((1, "MATEMATICA")), "Esercizi"),
+        ]
+    }
+
+    #[test]
+    fn detect_merges_the_agenda_and_the_lessons() {
+        let mut agenda = vec![
+            // no subject, ROSSI MARIO teaches both subjects and gives none
+            agenda(1, "AGNT", "2025-10-20T08:00:00+02:00", "Interrogazione"),
+            agenda(
+                2,
+                "AGNT",
+                "2025-10-16T08:00:00+02:00",
+                "Verifica di matematica",
+            ),
+            agenda(3, "AGNT", "2025-10-17T08:00:00+02:00", "Uscita didattica"),
+        ];
+        agenda[1].subjectId = Some(1);
+        agenda[1].subjectDesc = Some("MATEMATICA".to_string());
+        let mut lessons = lessons();
+        // the test of the agenda recorded again in the lessons
+        lessons.push(lesson("2025-10-16", 2, Some((1, "MATEMATICA")), "Verifica"));
+
+        let assessments = detect(&agenda, &lessons);
+        let found: Vec<(String, Option<u32>, String)> = assessments
+            .iter()
+            .map(|x| (x.date.to_string(), x.id, x.subject_name()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("2025-10-08".to_string(), None, "MATEMATICA".to_string()),
+                ("2025-10-16".to_string(), Some(2), "MATEMATICA".to_string()),
+                ("2025-10-20".to_string(), Some(1), String::new()),
+            ]
+        );
+    }
+
+    #[test]
+    fn topics_after_the_previous_test() {
+        let lessons = lessons();
+        let assessments = detect(&[], &lessons);
+        let test = Assessment {
+            id: Some(2),
+            date: NaiveDate::from_ymd_opt(2025, 10, 16).unwrap(),
+            subject: Some(Subject::new(Some(1), None, "MATEMATICA")),
+            text: "Verifica".to_string(),
+            teacher: "ROSSI MARIO".to_string(),
+        };
+
+        // neither the day of the test nor other subjects
+        let topics: Vec<String> = topics_since_previous(&test, &assessments, &lessons)
+            .into_iter()
+            .map(|x| x.topic)
+            .collect();
+        assert_eq!(topics, vec!["Derivate", "Integrali"]);
+
+        // the first test covers every topic before it
+        let topics = topics_since_previous(&assessments[0], &assessments, &lessons);
+        assert_eq!(topics.len(), 1);
+        assert_eq!(topics[0].topic, "Limiti");
+    }
+}
@@ -0,0 +1,284 @@
+// Module: calendar
+use crate::response_types::Calendar;
+use crate::USER_CONFIG;
+use chrono::{Datelike, Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+const CALENDAR_FILE: &str = "calendar.json";
+
+// weeks looked up when moving a range of dates to the first week with school days
+const MAX_WEEKS: i64 = 52;
+
+/*
+Readable label of the status of a day
+SD: school day
+HD: holiday
+NW: no lessons, e.g. a closure of the school
+*/
+pub fn status_label(status: &str) -> &str {
+    match status {
+        "SD" => "School day",
+        "HD" => "Holiday",
+        "NW" => "No lessons",
+        _ => status,
+    }
+}
+
+// whether a weekday has lessons following the daily hours of config.toml
+pub fn has_lessons(date: NaiveDate) -> bool {
+    let weekday = date.weekday().num_days_from_monday() as usize;
+    USER_CONFIG
+        .attendance_settings
+        .daily_hours
+        .get(weekday)
+        .is_some_and(|x| *x > 0)
+}
+
+// status of every day of the school year, keyed by date(YYYY-MM-DD)
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct SchoolCalendar {
+    // start of the school year(YYYYMMDD) the calendar belongs to
+    pub year: String,
+    pub days: BTreeMap<String, String>,
+    // date(YYYYMMDD) of a failed fetch, the calendar is empty until the next day
+    #[serde(default)]
+    pub failed_on: Option<String>,
+}
+
+impl SchoolCalendar {
+    pub fn from_calendar(calendar: Calendar, year: &str) -> Self {
+        SchoolCalendar {
+            year: year.to_string(),
+            days: calendar
+                .calendar
+                .into_iter()
+                .map(|x| (x.dayDate, x.dayStatus))
+                .collect(),
+            failed_on: None,
+        }
+    }
+
+    // load the calendar stored by the last update, None if it has never been stored
+    pub fn load() -> Option<Self> {
+        let file = std::fs::File::open(USER_CONFIG.data_path(CALENDAR_FILE)).ok()?;
+        serde_json::from_reader(file).ok()
+    }
+
+    // written to a temporary file first, so that an interrupted write leaves no broken calendar
+    pub fn store(&self) {
+        let path = USER_CONFIG.data_path(CALENDAR_FILE);
+        let temp_path = path.with_extension("json.tmp");
+        let file = match std::fs::File::create(&temp_path) {
+            Ok(v) => v,
+            Err(e) => panic!("error at creating the calendar file: {}", e),
+        };
+        match serde_json::to_writer(file, self) {
+            Ok(_) => (),
+            Err(e) => panic!("error at writing the calendar file: {}", e),
+        };
+        if let Err(e) = std::fs::rename(&temp_path, &path) {
+            panic!("error at replacing the calendar file: {}", e);
+        }
+    }
+
+    pub fn status(&self, date: NaiveDate) -> Option<&str> {
+        self.days
+            .get(&date.format("%Y-%m-%d").to_string())
+            .map(|x| x.as_str())
+    }
+
+    // the days missing from the calendar follow the daily hours of config.toml
+    pub fn is_school_day(&self, date: NaiveDate) -> bool {
+        match self.status(date) {
+            Some(status) => status == "SD",
+            None => has_lessons(date),
+        }
+    }
+
+    // school days after a date, up to another one included
+    pub fn school_days_between(&self, from: NaiveDate, to: NaiveDate) -> i64 {
+        let mut count = 0;
+        let mut date = from + Duration::days(1);
+        while date <= to {
+            if self.is_school_day(date) {
+                count += 1;
+            }
+            date += Duration::days(1);
+        }
+        count
+    }
+
+    // the first and the last school day of a range of dates, None if there are none
+    pub fn trim(&self, start: NaiveDate, end: NaiveDate) -> Option<(NaiveDate, NaiveDate)> {
+        let days: Vec<NaiveDate> = start
+            .iter_days()
+            .take_while(|x| *x <= end)
+            .filter(|x| self.is_school_day(*x))
+            .collect();
+        Some((*days.first()?, *days.last()?))
+    }
+
+    /*
+    Like trim, but a range without school days, e.g. during the holidays, moves to the first school day after it
+    the moved range ends on the same weekday as the original one, e.g. from the first monday to friday
+    */
+    pub fn next_school_range(&self, start: NaiveDate, end: NaiveDate) -> (NaiveDate, NaiveDate) {
+        if let Some(range) = self.trim(start, end) {
+            return range;
+        }
+
+        let first = match (end + Duration::days(1))
+            .iter_days()
+            .take((MAX_WEEKS * 7) as usize)
+            .find(|x| self.is_school_day(*x))
+        {
+            Some(v) => v,
+            None => return (start, end),
+        };
+        let monday = first - Duration::days(first.weekday().num_days_from_monday() as i64);
+        let mut last = monday + Duration::days(end.weekday().num_days_from_monday() as i64);
+        if last < first {
+            last += Duration::weeks(1);
+        }
+        self.trim(first, last).unwrap_or((first, first))
+    }
+
+    // the last school day of the calendar
+    pub fn last_school_day(&self) -> Option<NaiveDate> {
+        self.days
+            .iter()
+            .rev()
+            .find(|(_, status)| *status == "SD")
+            .and_then(|(date, _)| date.parse().ok())
+    }
+
+    /*
+    Periods without lessons which include days that would be school days otherwise
+    consecutive days without lessons are merged, e.g. the christmas holidays with their weekends
+    */
+    pub fn breaks(&self) -> Vec<(NaiveDate, NaiveDate, Vec<String>)> {
+        let mut breaks: Vec<(NaiveDate, NaiveDate, Vec<String>)> = Vec::new();
+        let mut last: Option<NaiveDate> = None;
+        for (date, status) in &self.days {
+            let date: NaiveDate = match date.parse() {
+                Ok(v) => v,
+                Err(_) => continue,
+            };
+            if status == "SD" {
+                last = None;
+                continue;
+            }
+
+            match breaks.last_mut() {
+                Some((_, end, statuses)) if last == Some(date - Duration::days(1)) => {
+                    *end = date;
+                    if !statuses.contains(status) {
+                        statuses.push(status.clone());
+                    }
+                }
+                _ => breaks.push((date, date, vec![status.clone()])),
+            }
+            last = Some(date);
+        }
+
+        // the weekends alone are not breaks
+        breaks
+            .into_iter()
+            .filter(|(start, end, _)| start.iter_days().take_while(|x| x <= end).any(has_lessons))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(text: &str) -> NaiveDate {
+        text.parse().unwrap()
+    }
+
+    // the days missing from the calendar follow the daily hours of config.example.toml, monday to friday
+    fn calendar(days: &[(&str, &str)]) -> SchoolCalendar {
+        SchoolCalendar {
+            year: "20250901".to_string(),
+            days: days
+                .iter()
+                .map(|(date, status)| (date.to_string(), status.to_string()))
+                .collect(),
+            failed_on: None,
+        }
+    }
+
+    // a closure on monday 2025-10-13, right after a weekend
+    fn october() -> SchoolCalendar {
+        calendar(&[
+            ("2025-10-10", "SD"),
+            ("2025-10-11", "HD"),
+            ("2025-10-12", "HD"),
+            ("2025-10-13", "NW"),
+            ("2025-10-14", "SD"),
+            ("2025-10-17", "SD"),
+            ("2025-10-18", "HD"),
+            ("2025-10-19", "HD"),
+        ])
+    }
+
+    #[test]
+    fn trim_keeps_the_school_days() {
+        let calendar = october();
+        assert_eq!(
+            calendar.trim(date("2025-10-11"), date("2025-10-19")),
+            Some((date("2025-10-14"), date("2025-10-17")))
+        );
+        assert_eq!(calendar.trim(date("2025-10-11"), date("2025-10-13")), None);
+    }
+
+    #[test]
+    fn next_school_range_skips_the_holidays() {
+        // christmas holidays until the epiphany, lessons start again on monday 2026-01-05
+        let days: Vec<(String, &str)> = date("2025-12-22")
+            .iter_days()
+            .take_while(|x| *x <= date("2026-01-06"))
+            .map(|x| match x == date("2026-01-05") {
+                true => (x.format("%Y-%m-%d").to_string(), "SD"),
+                false => (x.format("%Y-%m-%d").to_string(), "HD"),
+            })
+            .collect();
+        let days: Vec<(&str, &str)> = days.iter().map(|(d, s)| (d.as_str(), *s)).collect();
+        let calendar = calendar(&days);
+
+        assert_eq!(
+            calendar.next_school_range(date("2025-12-22"), date("2025-12-26")),
+            (date("2026-01-05"), date("2026-01-09"))
+        );
+        // from a wednesday the moved range still starts on monday
+        assert_eq!(
+            calendar.next_school_range(date("2025-12-24"), date("2025-12-26")),
+            (date("2026-01-05"), date("2026-01-09"))
+        );
+        // a range ending on tuesday stops on the tuesday after the first school day, the epiphany
+        assert_eq!(
+            calendar.next_school_range(date("2025-12-24"), date("2025-12-30")),
+            (date("2026-01-05"), date("2026-01-05"))
+        );
+        // a range with school days is only trimmed
+        assert_eq!(
+            calendar.next_school_range(date("2026-01-05"), date("2026-01-11")),
+            (date("2026-01-05"), date("2026-01-09"))
+        );
+    }
+
+    #[test]
+    fn breaks_merge_the_weekends() {
+        let breaks = october().breaks();
+        assert_eq!(
+            breaks,
+            vec![(
+                date("2025-10-11"),
+                date("2025-10-13"),
+                vec!["HD".to_string(), "NW".to_string()]
+            )]
+        );
+    }
+}
@@ -0,0 +1,249 @@
+// Module: credit
+use crate::response_types::Grade;
+use crate::stats::{average, rule_average};
+use crate::subjects::{normalize, subject_name};
+use crate::USER_CONFIG;
+use std::collections::BTreeMap;
+
+// credits of the 3rd, 4th and 5th year, None when no credit is given
+type Credits = [Option<(u8, u8)>; 3];
+
+/*
+Table A of D.Lgs. 62/2017, the credits are given by the average of the final grades
+every band is (lowest average excluded, highest average included, credits of the 3rd, 4th and 5th year)
+the students of the 3rd and 4th year with an average below 6 get no credits
+*/
+const CREDIT_BANDS: [(f64, f64, Credits); 6] = [
+    (0.0, 6.0, [None, None, Some((7, 8))]),
+    (6.0, 6.0, [Some((7, 8)), Some((8, 9)), Some((9, 10))]),
+    (6.0, 7.0, [Some((8, 9)), Some((9, 10)), Some((10, 11))]),
+    (7.0, 8.0, [Some((9, 10)), Some((10, 11)), Some((11, 12))]),
+    (8.0, 9.0, [Some((10, 11)), Some((11, 12)), Some((13, 14))]),
+    (9.0, 10.0, [Some((11, 12)), Some((12, 13)), Some((14, 15))]),
+];
+
+// maximum amount of credits of the three years
+pub const MAX_CREDITS: u8 = 40;
+
+pub struct Band {
+    pub label: String,
+    pub credits: Credits,
+    // how far the next band is, None for the highest band
+    pub next: Option<String>,
+}
+
+pub struct CreditReport {
+    // subject and report card grade
+    pub subjects: Vec<(String, u32)>,
+    pub average: f64,
+    pub band: Band,
+}
+
+// the band containing an average, an average of exactly 6 has its own band
+pub fn find_band(average: f64) -> Band {
+    let index = if average < 6.0 {
+        0
+    } else if average == 6.0 {
+        1
+    } else {
+        CREDIT_BANDS
+            .iter()
+            .rposition(|(low, high, _)| average > *low && average <= *high)
+            .unwrap_or(CREDIT_BANDS.len() - 1)
+    };
+
+    let (low, high, credits) = CREDIT_BANDS[index];
+    let label = match index {
+        0 => "M < 6".to_string(),
+        1 => "M = 6".to_string(),
+        _ => format!("{} < M ≤ {}", low, high),
+    };
+    let next = match index {
+        // 6 is enough to reach the next band
+        0 => Some(format!("{:.2} to reach an average of 6", 6.0 - average)),
+        // on the upper bound the next band starts right above, there is no distance to show
+        _ if index + 1 < CREDIT_BANDS.len() && average >= high => Some(format!(
+            "at the boundary, any average above {} reaches the next band",
+            high
+        )),
+        _ if index + 1 < CREDIT_BANDS.len() => {
+            Some(format!("{:.2} to go above {}", high - average, high))
+        }
+        _ => None,
+    };
+
+    Band {
+        label,
+        credits,
+        next,
+    }
+}
+
+// religion is evaluated with a judgement and it is not part of the average
+fn is_excluded(desc: &str) -> bool {
+    normalize(desc).starts_with("religione")
+}
+
+fn is_conduct(desc: &str) -> bool {
+    let desc = normalize(desc);
+    desc.contains("comportamento") || desc.contains("condotta")
+}
+
+/*
+Estimate the average of the final grades
+every subject gets the average of its grades rounded to an integer, like on the report card
+the conduct grade is taken from config.toml or from the grades if present
+*/
+pub fn estimate(grades: Vec<Grade>) -> Option<CreditReport> {
+    estimate_with_conduct(grades, USER_CONFIG.credit_settings.conduct)
+}
+
+fn estimate_with_conduct(grades: Vec<Grade>, conduct_grade: Option<u32>) -> Option<CreditReport> {
+    // grouped by id, two subjects with the same alias still get their own grade
+    let mut by_subject: BTreeMap<u32, (String, Vec<(f64, f64)>)> = BTreeMap::new();
+    let mut conduct: Vec<(f64, f64)> = Vec::new();
+    for grade in grades.iter().filter(|x| !x.canceled) {
+        if is_excluded(&grade.subjectDesc) {
+            continue;
+        }
+        if is_conduct(&grade.subjectDesc) {
+            conduct.push((grade.decimalValue, grade.weightFactor));
+            continue;
+        }
+
+        by_subject
+            .entry(grade.subjectId)
+            .or_insert_with(|| {
+                let subject = subject_name(
+                    Some(grade.subjectId),
+                    Some(&grade.subjectCode),
+                    &grade.subjectDesc,
+                );
+                (subject, Vec::new())
+            })
+            .1
+            .push((grade.decimalValue, grade.weightFactor));
+    }
+
+    let mut subjects: Vec<(String, u32)> = by_subject
+        .into_values()
+        .map(|(subject, values)| (subject, rule_average(&values)))
+        .filter(|(_, average)| !average.is_nan())
+        .map(|(subject, average)| (subject, average.round() as u32))
+        .collect();
+
+    let conduct = match conduct_grade {
+        Some(v) => Some(v),
+        None => Some(rule_average(&conduct))
+            .filter(|x| !x.is_nan())
+            .map(|x| x.round() as u32),
+    };
+    if let Some(conduct) = conduct {
+        subjects.push(("Comportamento".to_string(), conduct));
+    }
+
+    if subjects.is_empty() {
+        return None;
+    }
+
+    let values: Vec<f64> = subjects.iter().map(|(_, grade)| *grade as f64).collect();
+    let average = average(&values);
+
+    Some(CreditReport {
+        subjects,
+        average,
+        band: find_band(average),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::response_types::fixtures::grade;
+
+    fn grades() -> Vec<Grade> {
+        let mut canceled = grade(1, "MATEMATICA", "2025-10-08", 1.0);
+        canceled.canceled = true;
+        vec![
+            grade(1, "MATEMATICA", "2025-10-06", 6.0),
+            grade(1, "MATEMATICA", "2025-10-07", 7.0),
+            canceled,
+            // both shown as ITALIANO, the first one through the alias of config.example.toml
+            grade(2, "LINGUA E LETTERATURA ITALIANA", "2025-10-06", 8.0),
+            grade(3, "ITALIANO", "2025-10-06", 4.0),
+            grade(4, "RELIGIONE CATTOLICA", "2025-10-06", 10.0),
+            grade(5, "COMPORTAMENTO", "2025-10-06", 9.0),
+        ]
+    }
+
+    #[test]
+    fn estimate_rounds_every_subject() {
+        let report = estimate_with_conduct(grades(), None).unwrap();
+        assert_eq!(
+            report.subjects,
+            vec![
+                // 6.5 rounds up like on the report card
+                ("MATEMATICA".to_string(), 7),
+                ("ITALIANO".to_string(), 8),
+                ("ITALIANO".to_string(), 4),
+                ("Comportamento".to_string(), 9),
+            ]
+        );
+        assert_eq!(report.average, 7.0);
+        assert_eq!(report.band.label, "6 < M ≤ 7");
+    }
+
+    #[test]
+    fn estimate_with_the_conduct_of_config() {
+        let report = estimate_with_conduct(grades(), Some(6)).unwrap();
+        assert_eq!(
+            report.subjects.last(),
+            Some(&("Comportamento".to_string(), 6))
+        );
+        assert_eq!(report.average, 6.25);
+
+        // religion alone gives no estimate
+        let religion = vec![grade(4, "RELIGIONE CATTOLICA", "2025-10-06", 10.0)];
+        assert!(estimate_with_conduct(religion, None).is_none());
+    }
+
+    #[test]
+    fn band_below_six() {
+        let band = find_band(5.5);
+        assert_eq!(band.label, "M < 6");
+        assert_eq!(band.credits[0], None);
+        assert_eq!(band.credits[2], Some((7, 8)));
+        assert_eq!(band.next.as_deref(), Some("0.50 to reach an average of 6"));
+    }
+
+    #[test]
+    fn band_of_exactly_six() {
+        let band = find_band(6.0);
+        assert_eq!(band.label, "M = 6");
+        assert_eq!(band.credits[0], Some((7, 8)));
+        assert!(band.next.unwrap().starts_with("at the boundary"));
+    }
+
+    #[test]
+    fn band_upper_bound_is_included() {
+        let band = find_band(7.0);
+        assert_eq!(band.label, "6 < M ≤ 7");
+        assert_eq!(band.credits[1], Some((9, 10)));
+        assert!(band.next.unwrap().starts_with("at the boundary"));
+
+        let band = find_band(6.5);
+        assert_eq!(band.label, "6 < M ≤ 7");
+        assert_eq!(band.next.as_deref(), Some("0.50 to go above 7"));
+
+        let band = find_band(7.01);
+        assert_eq!(band.label, "7 < M ≤ 8");
+    }
+
+    #[test]
+    fn highest_band_has_no_next() {
+        let band = find_band(10.0);
+        assert_eq!(band.label, "9 < M ≤ 10");
+        assert_eq!(band.credits[2], Some((14, 15)));
+        assert!(band.next.is_none());
+    }
+}
@@ -1,11 +1,39 @@
-use crate::input::GradeSettings;
+use crate::absences::{
+    event_label, is_missed, missed_by_subject, reason_label, AbsenceKind, AbsenceSummary,
+};
+use crate::agenda::{event_label as agenda_event_label, AgendaKind};
+use crate::assessments::{detect, is_assessment, topics_since_previous, Assessment};
+use crate::calendar::{has_lessons, status_label, SchoolCalendar};
+use crate::credit::{estimate, MAX_CREDITS};
+use crate::documents::file_names;
+use crate::homework::Homework;
+use crate::input::{AbsenceSettings, AgendaFilter, AgendaSettings, GradeSettings, Settings};
+use crate::materials::Material;
+use crate::notes::NoteKind;
+use crate::planner::{to_ics, Plan, Session};
 use crate::response_types::*;
+use crate::risk::{assess, Severity};
+use crate::search::{highlight, search};
+use crate::stats::{current_averages, histogram, Summary};
+use crate::subjects::{
+    filter_by_name, filter_by_subjects, normalize, resolve_name, subject_name, Subject,
+};
+use crate::syllabus::{hours_by_subject, topics_by_subject};
+use crate::timetable::{current_slot, hour_start, next_slot, Timetable, WEEKDAYS};
+use crate::workload::{heat, Load};
 use crate::USER_CONFIG;
-use chrono::{DateTime, FixedOffset, NaiveDate};
+use chrono::{offset::Local, DateTime, Datelike, FixedOffset, NaiveDate, NaiveDateTime};
+use std::collections::{BTreeMap, HashMap};
+use std::io::IsTerminal;
 use tabled::{
-    settings::{object::Rows, Alignment, Modify, Style, Width},
+    builder::Builder,
+    settings::{
+        object::{Columns, Rows},
+        Alignment, Modify, Span, Style, Width,
+    },
     Table, Tabled,
 };
+use terminal_size::{terminal_size, Width as TerminalWidth};
 
 trait DefaultStyle {
     fn add_default_style(&mut self);
@@ -26,29 +54,22 @@ impl DefaultStyle for Table {
 #[allow(non_snake_case)]
 #[derive(Tabled)]
 pub struct SimpleGrade {
-    subject: String,
+    pub subject: String,
     date: String,
     pub grade: f64,
-    subject_type: String,
+    pub subject_type: String,
     pub weight: f64,
+    #[tabled(skip)]
+    pub canceled: bool,
 }
 
 impl SimpleGrade {
     fn from_grade(grade: Grade) -> Self {
-        let mut subject_desc = grade.subjectDesc;
-        if subject_desc.ends_with("sez. INFORMATICA") {
-            subject_desc = subject_desc.replace("sez. INFORMATICA", "");
-        }
-
-        let subject = match &subject_desc[..] {
-            "LINGUA E LETTERATURA ITALIANA" => "ITALIANO".to_string(),
-            "STORIA,CITTADINANZA E COSTITUZIONE" => "STORIA".to_string(),
-            "SCIENZE MOTORIE E SPORTIVE" => "MOTORIA".to_string(),
-            "TECNOLOGIE E PROGETTAZIONE DI SISTEMI INFORMATICI E DI TELECOMUNICAZIONI " => {
-                "TPSIT".to_string()
-            }
-            _ => subject_desc,
-        };
+        let subject = subject_name(
+            Some(grade.subjectId),
+            Some(&grade.subjectCode),
+            &grade.subjectDesc,
+        );
 
         SimpleGrade {
             subject,
@@ -56,6 +77,7 @@ impl SimpleGrade {
             grade: grade.decimalValue,
             subject_type: grade.componentDesc,
             weight: grade.weightFactor,
+            canceled: grade.canceled,
         }
     }
 }
@@ -89,74 +111,369 @@ fn sort_date_grade(grades: Vec<SimpleGrade>, from_oldest_to_youngest: bool) -> V
     grades
 }
 
-pub fn display_grades(grades: Grades, grade_settings: GradeSettings) -> (String, Vec<SimpleGrade>) {
-    let simplified_grades: Vec<SimpleGrade> = grades
-        .grades
+pub fn display_grades(
+    grades: Grades,
+    grade_settings: GradeSettings,
+) -> Result<(String, Vec<SimpleGrade>), String> {
+    if grades.grades.is_empty() {
+        return Ok((String::from("No records"), Vec::new()));
+    }
+
+    // filter it by name if the name is specified
+    let simplified_grades: Vec<SimpleGrade> =
+        filter_by_name(grades.grades, &grade_settings.name, |x| {
+            Some(Subject::new(
+                Some(x.subjectId),
+                Some(&x.subjectCode),
+                &x.subjectDesc,
+            ))
+        })?
         .into_iter()
         .map(SimpleGrade::from_grade)
         .collect();
 
-    if simplified_grades.is_empty() {
-        return (String::from("No records"), Vec::new());
+    let simplified_grades = sort_date_grade(simplified_grades, grade_settings.settings.desc_date);
+    let mut table = Table::new(&simplified_grades);
+    table.add_default_style();
+
+    // canceled grades are listed, but they count neither in the averages nor in the stats
+    let counted = simplified_grades
+        .into_iter()
+        .filter(|x| !x.canceled)
+        .collect();
+    Ok((table.to_string(), counted))
+}
+
+#[derive(Tabled)]
+struct GradeStats {
+    subject: String,
+    count: usize,
+    average: String,
+    median: String,
+    std_dev: String,
+    min: String,
+    max: String,
+    failing: String,
+}
+
+impl GradeStats {
+    fn from_summary(subject: String, summary: Summary) -> Self {
+        GradeStats {
+            subject,
+            count: summary.count,
+            average: format!("{:.2}", summary.average),
+            median: format!("{:.2}", summary.median),
+            std_dev: format!("{:.2}", summary.std_dev),
+            min: format!("{:.2}", summary.min),
+            max: format!("{:.2}", summary.max),
+            failing: format!("{} ({:.0}%)", summary.failing, summary.failing_share()),
+        }
     }
+}
 
-    // filter it by name if the name is specified
-    let simplified_grades: Vec<SimpleGrade> = match grade_settings.name {
-        Some(name) => simplified_grades
-            .into_iter()
-            .filter(|x| x.subject.to_lowercase().trim() == name.to_lowercase().trim())
-            .collect(),
-        None => simplified_grades,
+#[derive(Tabled)]
+struct ComponentStats {
+    subject: String,
+    component: String,
+    count: usize,
+    average: String,
+    failing: String,
+}
+
+// width of the longest bar of the histogram
+const HISTOGRAM_WIDTH: usize = 40;
+
+/*
+Statistics of the grades, for each subject and overall
+It is made of three parts: a summary table, a breakdown by component(Scritto/Orale/Pratico)
+and a histogram of the grade values
+*/
+pub fn display_grade_stats(grades: &[SimpleGrade]) -> String {
+    if grades.is_empty() {
+        return String::from("No records");
+    }
+
+    // group the values by subject and by subject and component
+    let mut by_subject: BTreeMap<&str, Vec<f64>> = BTreeMap::new();
+    let mut by_component: BTreeMap<(&str, &str), Vec<f64>> = BTreeMap::new();
+    let mut overall_by_component: BTreeMap<&str, Vec<f64>> = BTreeMap::new();
+    for grade in grades {
+        by_subject
+            .entry(&grade.subject)
+            .or_default()
+            .push(grade.grade);
+        by_component
+            .entry((&grade.subject, &grade.subject_type))
+            .or_default()
+            .push(grade.grade);
+        overall_by_component
+            .entry(&grade.subject_type)
+            .or_default()
+            .push(grade.grade);
+    }
+    let values: Vec<f64> = grades.iter().map(|x| x.grade).collect();
+
+    let mut summary_rows: Vec<GradeStats> = by_subject
+        .iter()
+        .filter_map(|(subject, values)| {
+            Summary::from_values(values).map(|x| GradeStats::from_summary(subject.to_string(), x))
+        })
+        .collect();
+    if let Some(summary) = Summary::from_values(&values) {
+        summary_rows.push(GradeStats::from_summary("Overall".to_string(), summary));
+    }
+
+    let component_rows: Vec<ComponentStats> = by_component
+        .iter()
+        .map(|((subject, component), values)| (subject.to_string(), component, values))
+        .chain(
+            overall_by_component
+                .iter()
+                .map(|(component, values)| ("Overall".to_string(), component, values)),
+        )
+        .filter_map(|(subject, component, values)| {
+            Summary::from_values(values).map(|summary| ComponentStats {
+                subject,
+                component: component.to_string(),
+                count: summary.count,
+                average: format!("{:.2}", summary.average),
+                failing: format!("{} ({:.0}%)", summary.failing, summary.failing_share()),
+            })
+        })
+        .collect();
+
+    let mut summary_table = Table::new(summary_rows);
+    summary_table.add_default_style();
+    let mut component_table = Table::new(component_rows);
+    component_table.add_default_style();
+
+    // draw the histogram, the longest bar is HISTOGRAM_WIDTH characters long
+    let buckets = histogram(&values);
+    let highest = *buckets.iter().max().unwrap_or(&1);
+    let mut chart = String::new();
+    for (i, count) in buckets.iter().enumerate() {
+        let length = count * HISTOGRAM_WIDTH / highest.max(1);
+        chart.push_str(&format!(
+            "{:>3} | {} {}\n",
+            i + 1,
+            "█".repeat(length),
+            count
+        ));
+    }
+
+    format!(
+        "{}\n\nBy component\n{}\n\nDistribution of the grades\n{}",
+        summary_table,
+        component_table,
+        chart.trim_end()
+    )
+}
+
+#[derive(Tabled)]
+struct SimpleRisk {
+    priority: String,
+    subject: String,
+    average: String,
+    goal: String,
+    reasons: String,
+}
+
+// display the subjects at risk, the most severe flag is returned to compute the exit code
+pub fn display_risks(
+    grades: Grades,
+    grade_settings: GradeSettings,
+) -> Result<(String, Option<Severity>), String> {
+    let grades = filter_by_name(grades.grades, &grade_settings.name, |x| {
+        Some(Subject::new(
+            Some(x.subjectId),
+            Some(&x.subjectCode),
+            &x.subjectDesc,
+        ))
+    })?;
+
+    let report = assess(grades);
+    if report.risks.is_empty() {
+        return Ok((String::from("No subjects at risk"), None));
+    }
+
+    let severity = report.risks.iter().map(|x| x.severity()).max();
+    let simplified_risks: Vec<SimpleRisk> = report
+        .risks
+        .into_iter()
+        .map(|x| SimpleRisk {
+            priority: x.severity().label().to_string(),
+            average: match x.average {
+                Some(average) => format!("{:.2}", average),
+                None => "N/A".to_string(),
+            },
+            goal: format!("{:.2}", x.goal),
+            reasons: x
+                .reasons
+                .into_iter()
+                .map(|(_, reason)| reason)
+                .collect::<Vec<String>>()
+                .join("\n"),
+            subject: x.subject,
+        })
+        .collect();
+
+    let mut table = Table::new(simplified_risks);
+    table.add_default_style();
+
+    Ok((
+        format!("Current period: {}\n{}", report.period, table),
+        severity,
+    ))
+}
+
+#[derive(Tabled)]
+struct SimpleFinalGrade {
+    subject: String,
+    grade: u32,
+}
+
+// display the estimated credito scolastico of the student
+pub fn display_credit(grades: Grades) -> String {
+    let report = match estimate(grades.grades) {
+        Some(v) => v,
+        None => return String::from("No records"),
     };
+    let credit_settings = &USER_CONFIG.credit_settings;
 
-    let simplified_grades = sort_date_grade(simplified_grades, grade_settings.settings.desc_date);
-    let mut table = Table::new(&simplified_grades);
+    let simplified_grades: Vec<SimpleFinalGrade> = report
+        .subjects
+        .into_iter()
+        .map(|(subject, grade)| SimpleFinalGrade { subject, grade })
+        .collect();
+    let mut table = Table::new(simplified_grades);
     table.add_default_style();
 
-    (table.to_string(), simplified_grades)
+    let mut lines: Vec<String> = vec![
+        table.to_string(),
+        format!("Estimated average: {:.2}", report.average),
+        format!("Band: {}", report.band.label),
+    ];
+
+    // show every year when the year of the student is not configured
+    let years: Vec<u8> = match credit_settings.year {
+        Some(year) => vec![year],
+        None => vec![3, 4, 5],
+    };
+    for year in years {
+        let credits = report.band.credits[(year - 3) as usize];
+        lines.push(match credits {
+            Some((low, high)) => format!("Credits of the year {}: {}-{}", year, low, high),
+            None => format!("Credits of the year {}: none, the average is below 6", year),
+        });
+
+        if let (Some(past_credits), Some(_)) = (credit_settings.past_credits, credit_settings.year)
+        {
+            let (low, high) = credits.unwrap_or((0, 0));
+            lines.push(format!(
+                "Total credits: {}-{} out of {}",
+                past_credits + low as u32,
+                past_credits + high as u32,
+                MAX_CREDITS
+            ));
+        }
+    }
+
+    match report.band.next {
+        Some(next) => lines.push(format!("Next band: {}", next)),
+        None => lines.push("This is the highest band".to_string()),
+    }
+
+    lines.join("\n")
 }
 
 #[allow(non_snake_case)]
 #[derive(Tabled)]
 struct SimpleAbsence {
-    id: String,
+    kind: String,
     date: String,
     justified: bool,
     reason: String,
     code: String,
+    // days since an unjustified event
+    pending: String,
 }
 
 impl SimpleAbsence {
     fn from_absence(absence: Absence) -> Self {
-        let (reason, code) = if absence.justifReasonDesc.is_none() {
-            ("N/A".to_string(), "N/A".to_string())
-        } else {
-            (
-                absence.justifReasonDesc.unwrap(),
-                absence.justifReasonCode.unwrap(),
-            )
+        let code = absence.justifReasonCode.unwrap_or("N/A".to_string());
+        // the description of the server comes first, the label of the code is a fallback
+        let desc = absence.justifReasonDesc.filter(|x| !x.trim().is_empty());
+        let reason = match (desc, reason_label(&code)) {
+            (Some(desc), _) => desc,
+            (None, Some(label)) => label.to_string(),
+            (None, None) => "N/A".to_string(),
         };
 
-        let type_absence = match &absence.evtCode[..] {
-            "ABA0" => "Assenza",
-            "ABR0" => "Ritardo",
-            "ABR1" => "R. Breve",
-            _ => "",
+        let pending = if absence.isJustified {
+            String::new()
+        } else {
+            match NaiveDate::parse_from_str(&absence.evtDate, "%Y-%m-%d") {
+                Ok(date) => format!("{} days", (Local::now().date_naive() - date).num_days()),
+                Err(_) => String::new(),
+            }
         };
 
         SimpleAbsence {
-            id: type_absence.to_string(),
+            kind: event_label(&absence.evtCode).to_string(),
             date: absence.evtDate,
             justified: absence.isJustified,
             reason,
             code,
+            pending,
         }
     }
 }
 
-pub fn display_absences(absences: Absences) -> String {
-    let simplified_absences: Vec<SimpleAbsence> = absences
-        .events
+/*
+Keep the absences matching the filters of the absence command
+with lessons, only the absences during which a lesson of the selected subjects has been missed
+*/
+pub fn filter_absences(
+    mut events: Vec<Absence>,
+    lessons: Option<&Lessons>,
+    absence_settings: &AbsenceSettings,
+) -> Result<Vec<Absence>, String> {
+    if let (Some(lessons), Some(_)) = (lessons, &absence_settings.name) {
+        let lessons = filter_by_name(
+            lessons.lessons.iter().collect(),
+            &absence_settings.name,
+            |x: &&Lesson| Subject::from_lesson(x),
+        )?;
+        events.retain(|absence| lessons.iter().any(|lesson| is_missed(absence, lesson)));
+    }
+
+    events.retain(|absence| {
+        let date = NaiveDate::parse_from_str(&absence.evtDate, "%Y-%m-%d").ok();
+        (!absence_settings.unjustified || !absence.isJustified)
+            && absence_settings
+                .kind
+                .is_none_or(|kind| AbsenceKind::from_code(&absence.evtCode) == Some(kind))
+            && absence_settings
+                .from
+                .is_none_or(|from| date.is_some_and(|x| x >= from))
+            && absence_settings
+                .to
+                .is_none_or(|to| date.is_some_and(|x| x <= to))
+    });
+    Ok(events)
+}
+
+// the absences already filtered by filter_absences
+pub fn display_absences(absences: Absences, absence_settings: AbsenceSettings) -> String {
+    let mut events = absences.events;
+
+    if absence_settings.settings.desc_date {
+        events.sort_by(|a, b| b.evtDate.cmp(&a.evtDate));
+    } else {
+        events.sort_by(|a, b| a.evtDate.cmp(&b.evtDate));
+    }
+
+    let simplified_absences: Vec<SimpleAbsence> = events
         .into_iter()
         .map(SimpleAbsence::from_absence)
         .collect();
@@ -171,13 +488,120 @@ pub fn display_absences(absences: Absences) -> String {
     table.to_string()
 }
 
+#[derive(Tabled)]
+struct SimpleCount {
+    kind: String,
+    count: usize,
+}
+
+// display the absences counted against the limit of the annual hours
+pub fn display_absence_summary(absences: Absences) -> String {
+    let attendance_settings = &USER_CONFIG.attendance_settings;
+    let summary = AbsenceSummary::from_absences(&absences.events);
+
+    let counts = vec![
+        SimpleCount {
+            kind: "Assenze".to_string(),
+            count: summary.full_days,
+        },
+        SimpleCount {
+            kind: "Ritardi".to_string(),
+            count: summary.late_entries,
+        },
+        SimpleCount {
+            kind: "Ritardi brevi".to_string(),
+            count: summary.short_delays,
+        },
+        SimpleCount {
+            kind: "Uscite".to_string(),
+            count: summary.early_exits,
+        },
+    ];
+    let mut table = Table::new(counts);
+    table.add_default_style();
+
+    let share = summary.missed_share();
+    let mut lines: Vec<String> = vec![
+        table.to_string(),
+        format!(
+            "Missed hours: {} out of {} ({:.1}%)",
+            summary.missed_hours, attendance_settings.annual_hours, share
+        ),
+    ];
+
+    let remaining = summary.remaining_hours();
+    if remaining >= 0 {
+        lines.push(format!(
+            "Hours left before reaching the {}% limit: {}",
+            attendance_settings.limit, remaining
+        ));
+    } else {
+        lines.push(format!(
+            "[WARNING]: the {}% limit has been exceeded by {} hours",
+            attendance_settings.limit, -remaining
+        ));
+    }
+
+    if remaining >= 0 && share >= attendance_settings.warning {
+        lines.push(format!(
+            "[WARNING]: more than {}% of the hours have been missed",
+            attendance_settings.warning
+        ));
+    }
+
+    lines.join("\n")
+}
+
+#[derive(Tabled)]
+struct SimpleSubjectAttendance {
+    subject: String,
+    missed: u32,
+    held: u32,
+    share: String,
+}
+
+// display the hours missed for each subject, the lessons tell which subjects were missed
+pub fn display_absences_by_subject(
+    absences: Absences,
+    lessons: Lessons,
+    absence_settings: AbsenceSettings,
+) -> Result<String, String> {
+    let lessons = filter_by_name(
+        lessons.lessons,
+        &absence_settings.name,
+        Subject::from_lesson,
+    )?;
+    let attendance = missed_by_subject(&absences.events, &lessons);
+
+    if attendance.is_empty() {
+        return Ok(String::from("No records"));
+    }
+
+    let simplified_attendance: Vec<SimpleSubjectAttendance> = attendance
+        .into_iter()
+        .map(|x| SimpleSubjectAttendance {
+            share: format!("{:.1}%", x.missed_share()),
+            subject: x.subject,
+            missed: x.missed_hours,
+            held: x.held_hours,
+        })
+        .collect();
+
+    let mut table = Table::new(simplified_attendance);
+    table.add_default_style();
+
+    Ok(table.to_string())
+}
+
 #[allow(non_snake_case)]
 #[derive(Tabled)]
 struct SimpleAgenda {
     #[tabled(skip)]
     time: DateTime<FixedOffset>,
     date: String,
-    code: String,
+    time_range: String,
+    kind: String,
+    subject: String,
     notes: String,
     teacher: String,
 }
@@ -186,13 +610,32 @@ impl SimpleAgenda {
     fn from_agenda(agenda: Agenda) -> Self {
         let processed_time =
             DateTime::parse_from_str(&agenda.evtDatetimeBegin, "%Y-%m-%dT%H:%M:%S%z").unwrap();
+        let end_time = DateTime::parse_from_str(&agenda.evtDatetimeEnd, "%Y-%m-%dT%H:%M:%S%z");
+
+        let time_range = match (agenda.isFullDay, end_time) {
+            (true, _) => "Full day".to_string(),
+            (false, Ok(end)) if end > processed_time => format!(
+                "{} - {}",
+                processed_time.format("%H:%M"),
+                end.format("%H:%M")
+            ),
+            _ => processed_time.format("%H:%M").to_string(),
+        };
+
+        // the code is shown when the kind does not tell everything, e.g. reservations
+        let kind = AgendaKind::from_agenda(&agenda);
+        let kind = match (kind, agenda_event_label(&agenda.evtCode)) {
+            (AgendaKind::Event, label) => label.to_string(),
+            (kind, _) => kind.label().to_string(),
+        };
+
         SimpleAgenda {
             time: processed_time,
-            code: match &agenda.evtCode[..] {
-                "AGHW" => "Homework".to_string(),
-                "AGNT" => "Nota".to_string(),
-                _ => agenda.evtCode,
-            },
+            time_range,
+            kind,
+            subject: Subject::from_agenda(&agenda)
+                .map(|x| x.name())
+                .unwrap_or_default(),
             teacher: agenda.authorName,
             notes: agenda.notes,
             date: "".to_string(),
@@ -200,18 +643,37 @@ impl SimpleAgenda {
     }
 }
 
-pub fn display_agenda(agenda: Agendas) -> String {
-    let mut simplified_agenda: Vec<SimpleAgenda> = agenda
-        .agenda
-        .into_iter()
-        .map(SimpleAgenda::from_agenda)
-        .collect();
+pub fn display_agenda(
+    agenda: Agendas,
+    agenda_settings: AgendaSettings,
+    agenda_filter: AgendaFilter,
+) -> Result<String, String> {
+    let mut agenda = agenda.agenda;
+    agenda.retain(|x| {
+        agenda_filter
+            .kind
+            .is_none_or(|kind| AgendaKind::from_agenda(x) == kind)
+            && agenda_filter
+                .teacher
+                .as_ref()
+                .is_none_or(|teacher| normalize(&x.authorName).contains(&normalize(teacher)))
+    });
+
+    let mut simplified_agenda: Vec<SimpleAgenda> =
+        filter_by_name(agenda, &agenda_settings.name, Subject::from_agenda)?
+            .into_iter()
+            .map(SimpleAgenda::from_agenda)
+            .collect();
 
     if simplified_agenda.is_empty() {
-        return String::from("No records");
+        return Ok(String::from("No records"));
     }
 
-    simplified_agenda.sort_by(|a, b| a.time.cmp(&b.time));
+    if agenda_settings.settings.desc_date {
+        simplified_agenda.sort_by_key(|x| std::cmp::Reverse(x.time));
+    } else {
+        simplified_agenda.sort_by_key(|x| x.time);
+    }
 
     for record in simplified_agenda.iter_mut() {
         record.date = record.time.format("%Y-%m-%d %A").to_string();
@@ -220,7 +682,7 @@ pub fn display_agenda(agenda: Agendas) -> String {
     let mut table = Table::new(simplified_agenda);
     table.add_default_style();
 
-    table.to_string()
+    Ok(table.to_string())
 }
 
 #[allow(non_snake_case)]
@@ -229,6 +691,8 @@ struct SimpleLesson {
     #[tabled(skip)]
     time: DateTime<FixedOffset>,
     date: String,
+    hour: u32,
+    subject: String,
     desc: String,
     code: String,
     teacher: String,
@@ -246,8 +710,16 @@ impl SimpleLesson {
         let fixed_offset = FixedOffset::east_opt(0).unwrap();
 
         let processed_time = DateTime::<FixedOffset>::from_utc(naive_time, fixed_offset);
+
+        let subject = match Subject::from_lesson(&lesson) {
+            Some(subject) => subject.name(),
+            None => String::new(),
+        };
+
         SimpleLesson {
             time: processed_time,
+            hour: lesson.evtHPos,
+            subject,
             desc: lesson.lessonArg,
             teacher: lesson.authorName,
             code: lesson.evtCode,
@@ -256,18 +728,26 @@ impl SimpleLesson {
     }
 }
 
-pub fn display_lessons(lessons: Lessons) -> String {
-    let mut simplified_lessons: Vec<SimpleLesson> = lessons
-        .lessons
-        .into_iter()
-        .map(SimpleLesson::from_lesson)
-        .collect();
+pub fn display_lessons(
+    lessons: Lessons,
+    lesson_settings: AgendaSettings,
+) -> Result<String, String> {
+    let mut simplified_lessons: Vec<SimpleLesson> =
+        filter_by_name(lessons.lessons, &lesson_settings.name, Subject::from_lesson)?
+            .into_iter()
+            .map(SimpleLesson::from_lesson)
+            .collect();
 
     if simplified_lessons.is_empty() {
-        return String::from("No records");
+        return Ok(String::from("No records"));
     }
 
-    simplified_lessons.sort_by(|a, b| a.time.cmp(&b.time));
+    // sort by date, then by hour
+    if lesson_settings.settings.desc_date {
+        simplified_lessons.sort_by(|a, b| b.time.cmp(&a.time).then(a.hour.cmp(&b.hour)));
+    } else {
+        simplified_lessons.sort_by_key(|x| (x.time, x.hour));
+    }
 
     for record in simplified_lessons.iter_mut() {
         record.date = record.time.format("%Y-%m-%d %A").to_string();
@@ -276,5 +756,1526 @@ pub fn display_lessons(lessons: Lessons) -> String {
     let mut table = Table::new(simplified_lessons);
     table.add_default_style();
 
+    Ok(table.to_string())
+}
+
+// width of the terminal, used to fit the grid of the lessons
+fn terminal_width() -> usize {
+    match terminal_size() {
+        Some((TerminalWidth(width), _)) => width as usize,
+        None => 120,
+    }
+}
+
+/*
+Display the lessons as a week calendar, days as columns and hour positions as rows
+a lesson lasting more than one hour spans more rows
+every cell shows the subject, the teacher and the topic of the lesson
+*/
+pub fn display_lesson_grid(
+    lessons: Lessons,
+    lesson_settings: AgendaSettings,
+) -> Result<String, String> {
+    let lessons = filter_by_name(lessons.lessons, &lesson_settings.name, Subject::from_lesson)?;
+    if lessons.is_empty() {
+        return Ok(String::from("No records"));
+    }
+
+    let mut days: Vec<&String> = lessons.iter().map(|x| &x.evtDate).collect();
+    days.sort();
+    days.dedup();
+    // some schools number the first hour 0
+    let first_hour = lessons.iter().map(|x| x.evtHPos).min().unwrap_or(1);
+    let last_hour = lessons
+        .iter()
+        .map(|x| x.evtHPos + x.evtDuration.max(1) - 1)
+        .max()
+        .unwrap_or(1);
+
+    // cell content and row span for each (hour, day), more lessons in the same hour share the cell
+    let mut cells: BTreeMap<(u32, usize), (Vec<String>, u32)> = BTreeMap::new();
+    for lesson in &lessons {
+        let day = days.iter().position(|x| **x == lesson.evtDate).unwrap();
+        let subject = Subject::from_lesson(lesson)
+            .map(|x| x.name())
+            .unwrap_or_default();
+        let text = format!("{}\n{}\n{}", subject, lesson.authorName, lesson.lessonArg);
+
+        let cell = cells
+            .entry((lesson.evtHPos, day))
+            .or_insert((Vec::new(), 1));
+        if !cell.0.contains(&text) {
+            cell.0.push(text);
+        }
+        cell.1 = cell.1.max(lesson.evtDuration.max(1));
+    }
+
+    // a lesson starting inside the span of another one joins its cell, spans never overlap
+    let mut merged: BTreeMap<(u32, usize), (Vec<String>, u32)> = BTreeMap::new();
+    let mut covering: HashMap<usize, u32> = HashMap::new();
+    for ((hour, day), (texts, span)) in cells {
+        let start = match covering.get(&day) {
+            Some(start) if merged[&(*start, day)].1 + start > hour => *start,
+            _ => {
+                covering.insert(day, hour);
+                merged.insert((hour, day), (Vec::new(), 1));
+                hour
+            }
+        };
+        let cell = merged.get_mut(&(start, day)).unwrap();
+        for text in texts {
+            if !cell.0.contains(&text) {
+                cell.0.push(text);
+            }
+        }
+        cell.1 = cell.1.max(hour + span - start);
+    }
+    let cells = merged;
+
+    let mut builder = Builder::default();
+    let mut header = vec![String::new()];
+    for day in &days {
+        header.push(match NaiveDate::parse_from_str(day, "%Y-%m-%d") {
+            Ok(date) => date.format("%a %d/%m").to_string(),
+            Err(_) => day.to_string(),
+        });
+    }
+    builder.set_header(header);
+    for hour in first_hour..=last_hour {
+        let mut row = vec![hour.to_string()];
+        for day in 0..days.len() {
+            row.push(match cells.get(&(hour, day)) {
+                Some((texts, _)) => texts.join("\n\n"),
+                None => String::new(),
+            });
+        }
+        builder.push_record(row);
+    }
+
+    // the hour column takes 4 characters, every column has a border and a padding of 2
+    let day_width = (terminal_width().saturating_sub(4 + 1) / days.len())
+        .saturating_sub(3)
+        .max(8);
+
+    let mut table = builder.build();
+    table
+        .with(Style::modern())
+        .with(Modify::new(Rows::first()).with(Alignment::center()))
+        .with(Modify::new(Columns::new(1..)).with(Width::wrap(day_width).keep_words()));
+    for ((hour, day), (_, span)) in cells {
+        if span > 1 {
+            let row = (hour - first_hour + 1) as usize;
+            table.with(Modify::new((row, day + 1)).with(Span::row(span as usize)));
+        }
+    }
+
+    Ok(table.to_string())
+}
+
+// display the weekly timetable, days as columns and hour positions as rows
+pub fn display_timetable(timetable: &Timetable) -> String {
+    let days: Vec<_> = WEEKDAYS
+        .iter()
+        .filter_map(|x| timetable.subjects_of(*x).map(|subjects| (x, subjects)))
+        .collect();
+    if days.is_empty() {
+        return String::from("No records");
+    }
+
+    let last_hour = days
+        .iter()
+        .filter_map(|(_, subjects)| subjects.keys().max())
+        .max()
+        .cloned()
+        .unwrap_or(1);
+
+    let mut builder = Builder::default();
+    let mut header = vec![String::new()];
+    header.extend(days.iter().map(|(weekday, _)| weekday.to_string()));
+    builder.set_header(header);
+    for hour in 1..=last_hour {
+        let mut row = vec![match hour_start(hour) {
+            Some(start) => format!("{} {}", hour, start.format("%H:%M")),
+            None => hour.to_string(),
+        }];
+        row.extend(
+            days.iter()
+                .map(|(_, subjects)| subjects.get(&hour).map(|x| x.name()).unwrap_or_default()),
+        );
+        builder.push_record(row);
+    }
+
+    let mut table = builder.build();
+    table.add_default_style();
+
     table.to_string()
 }
+
+// display the lesson taking place now
+pub fn display_now(timetable: &Timetable, now: NaiveDateTime) -> String {
+    match current_slot(timetable, now) {
+        Some(slot) => format!(
+            "Now: {} ({}° hour, since {})",
+            slot.subject,
+            slot.position,
+            slot.start.format("%H:%M")
+        ),
+        None => String::from("No lesson now"),
+    }
+}
+
+// display the next lesson and the subjects of its day, e.g. to know which books to pack
+pub fn display_next(timetable: &Timetable, now: NaiveDateTime) -> String {
+    let slot = match next_slot(timetable, now) {
+        Some(v) => v,
+        None => return String::from("No lessons in the timetable"),
+    };
+
+    let mut subjects: Vec<String> = Vec::new();
+    if let Some(day) = timetable.subjects_of(slot.start.weekday()) {
+        for subject in day.values().map(|x| x.name()) {
+            if !subjects.contains(&subject) {
+                subjects.push(subject);
+            }
+        }
+    }
+
+    format!(
+        "Next: {} ({}° hour, {})\nSubjects of {}: {}",
+        slot.subject,
+        slot.position,
+        slot.start.format("%A %H:%M"),
+        slot.start.format("%A %Y-%m-%d"),
+        subjects.join(", ")
+    )
+}
+
+#[derive(Tabled)]
+struct SimpleTopic {
+    subject: String,
+    dates: String,
+    hours: u32,
+    topic: String,
+}
+
+// display the topics covered for each subject, as a table or as markdown
+pub fn display_syllabus(
+    lessons: Lessons,
+    lesson_settings: AgendaSettings,
+    markdown: bool,
+) -> Result<String, String> {
+    let lessons = filter_by_name(lessons.lessons, &lesson_settings.name, Subject::from_lesson)?;
+    let syllabus = topics_by_subject(&lessons);
+    if syllabus.is_empty() {
+        return Ok(String::from("No records"));
+    }
+
+    if markdown {
+        let mut lines: Vec<String> = vec!["# Syllabus".to_string()];
+        for (subject, topics) in syllabus {
+            lines.push(format!("\n## {}\n", subject));
+            for topic in topics {
+                lines.push(format!(
+                    "- **{}** ({}h): {}",
+                    topic.dates(),
+                    topic.hours,
+                    topic.topic.replace('\n', " ")
+                ));
+            }
+        }
+        return Ok(lines.join("\n"));
+    }
+
+    let simplified_topics: Vec<SimpleTopic> = syllabus
+        .into_iter()
+        .flat_map(|(subject, topics)| {
+            topics.into_iter().map(move |topic| SimpleTopic {
+                subject: subject.clone(),
+                dates: topic.dates(),
+                hours: topic.hours,
+                topic: topic.topic,
+            })
+        })
+        .collect();
+
+    let mut table = Table::new(simplified_topics);
+    table.add_default_style();
+
+    Ok(table.to_string())
+}
+
+// time left until a date, counting only the school days
+fn days_left(today: NaiveDate, date: NaiveDate, calendar: &SchoolCalendar) -> String {
+    match (date - today).num_days() {
+        0 => "today".to_string(),
+        1 => "tomorrow".to_string(),
+        _ => match calendar.school_days_between(today, date) {
+            1 => "1 school day".to_string(),
+            days => format!("{} school days", days),
+        },
+    }
+}
+
+#[derive(Tabled)]
+struct SimpleExam {
+    date: String,
+    days: String,
+    subject: String,
+    test: String,
+    topics: String,
+    teacher: String,
+}
+
+// upcoming tests with the days remaining and the topics covered since the previous test
+pub fn display_exams(
+    lessons: Lessons,
+    agenda: Agendas,
+    name: &Option<String>,
+    today: NaiveDate,
+    calendar: &SchoolCalendar,
+) -> Result<String, String> {
+    let assessments = detect(&agenda.agenda, &lessons.lessons);
+    let upcoming: Vec<&Assessment> = assessments.iter().filter(|x| x.date >= today).collect();
+    let upcoming = filter_by_name(upcoming, name, |x| x.subject.clone())?;
+    if upcoming.is_empty() {
+        return Ok(String::from("No upcoming tests"));
+    }
+
+    let simplified_exams: Vec<SimpleExam> = upcoming
+        .into_iter()
+        .map(|assessment| {
+            let days = days_left(today, assessment.date, calendar);
+            let topics: Vec<String> =
+                topics_since_previous(assessment, &assessments, &lessons.lessons)
+                    .into_iter()
+                    .map(|x| x.topic.replace('\n', " "))
+                    .collect();
+            SimpleExam {
+                date: assessment.date.format("%Y-%m-%d %A").to_string(),
+                days,
+                subject: assessment.subject_name(),
+                test: assessment.text.clone(),
+                topics: topics.join("; "),
+                teacher: assessment.teacher.clone(),
+            }
+        })
+        .collect();
+
+    let mut table = Table::new(simplified_exams);
+    table.add_default_style();
+
+    Ok(table.to_string())
+}
+
+#[derive(Tabled)]
+struct SimpleHomework {
+    id: u32,
+    due: String,
+    status: String,
+    notes: String,
+    teacher: String,
+}
+
+impl SimpleHomework {
+    fn from_homework(homework: Homework, today: NaiveDate, calendar: &SchoolCalendar) -> Self {
+        let status = if homework.done {
+            "done".to_string()
+        } else if homework.is_overdue(today) {
+            "overdue".to_string()
+        } else {
+            days_left(today, homework.due, calendar)
+        };
+
+        SimpleHomework {
+            id: homework.id,
+            due: homework.due.format("%Y-%m-%d %A").to_string(),
+            status,
+            notes: homework.notes,
+            teacher: homework.teacher,
+        }
+    }
+}
+
+// homework grouped by subject, the subjects with the closest due date first
+pub fn display_homework(
+    homework: Vec<Homework>,
+    name: &Option<String>,
+    today: NaiveDate,
+    calendar: &SchoolCalendar,
+) -> Result<String, String> {
+    let homework = filter_by_name(homework, name, |x| x.subject.clone())?;
+    if homework.is_empty() {
+        return Ok(String::from("No homework"));
+    }
+
+    // homework is sorted by due date, so the subjects are in order of their first homework
+    let mut groups: Vec<(String, Vec<SimpleHomework>)> = Vec::new();
+    for homework in homework {
+        let subject = homework
+            .subject
+            .as_ref()
+            .map(|x| x.name())
+            .unwrap_or_else(|| "Other".to_string());
+        let record = SimpleHomework::from_homework(homework, today, calendar);
+        match groups.iter_mut().find(|(x, _)| *x == subject) {
+            Some((_, records)) => records.push(record),
+            None => groups.push((subject, vec![record])),
+        }
+    }
+
+    Ok(groups
+        .into_iter()
+        .map(|(subject, records)| {
+            let mut table = Table::new(records);
+            table.add_default_style();
+            format!("{}\n{}", subject, table)
+        })
+        .collect::<Vec<String>>()
+        .join("\n\n"))
+}
+
+#[derive(Tabled)]
+struct SimpleWorkload {
+    subject: String,
+    homework: usize,
+    tests: usize,
+    days: String,
+}
+
+/*
+Display the homework and the tests of the coming weeks
+a calendar with the amount of each day, and the amount of each subject with its days
+*/
+pub fn display_workload(
+    loads: Vec<Load>,
+    name: &Option<String>,
+    (start, end): (NaiveDate, NaiveDate),
+) -> Result<String, String> {
+    let loads = filter_by_name(loads, name, |x| x.subject.clone())?;
+
+    let mut by_day: BTreeMap<NaiveDate, (usize, usize)> = BTreeMap::new();
+    for load in &loads {
+        let day = by_day.entry(load.date).or_default();
+        if load.test {
+            day.1 += 1;
+        } else {
+            day.0 += 1;
+        }
+    }
+
+    let mut builder = Builder::default();
+    builder.set_header(WEEKDAYS.iter().map(|x| x.to_string()));
+    let mut week_start = start;
+    while week_start <= end {
+        let row = (0..7).map(|offset| {
+            let date = week_start + chrono::Duration::days(offset);
+            if date > end {
+                return String::new();
+            }
+            let (homework, tests) = by_day.get(&date).cloned().unwrap_or_default();
+            let mut cell = format!(
+                "{}\n{} {}",
+                date.format("%d/%m"),
+                heat(homework + tests),
+                homework + tests
+            );
+            if tests > 0 {
+                cell.push_str(&format!("\n{} test", tests));
+            }
+            cell
+        });
+        builder.push_record(row);
+        week_start += chrono::Duration::weeks(1);
+    }
+
+    let mut table = builder.build();
+    table
+        .with(Style::modern())
+        .with(Modify::new(Rows::first()).with(Alignment::center()));
+    let mut sections = vec![table.to_string()];
+
+    // subjects with the most homework and tests first
+    let mut by_subject: BTreeMap<String, Vec<&Load>> = BTreeMap::new();
+    for load in &loads {
+        let subject = load
+            .subject
+            .as_ref()
+            .map(|x| x.name())
+            .unwrap_or_else(|| "Other".to_string());
+        by_subject.entry(subject).or_default().push(load);
+    }
+    let mut simplified_workload: Vec<SimpleWorkload> = by_subject
+        .into_iter()
+        .map(|(subject, mut loads)| {
+            loads.sort_by_key(|x| x.date);
+            let mut days: Vec<(NaiveDate, usize)> = Vec::new();
+            for load in &loads {
+                match days.last_mut() {
+                    Some((date, count)) if *date == load.date => *count += 1,
+                    _ => days.push((load.date, 1)),
+                }
+            }
+            let days: Vec<String> = days
+                .into_iter()
+                .map(|(date, count)| match count {
+                    1 => date.format("%a %d/%m").to_string(),
+                    _ => format!("{} ({})", date.format("%a %d/%m"), count),
+                })
+                .collect();
+            SimpleWorkload {
+                subject,
+                homework: loads.iter().filter(|x| !x.test).count(),
+                tests: loads.iter().filter(|x| x.test).count(),
+                days: days.join(", "),
+            }
+        })
+        .collect();
+    if !simplified_workload.is_empty() {
+        simplified_workload.sort_by_key(|x| std::cmp::Reverse(x.homework + x.tests));
+        let mut table = Table::new(simplified_workload);
+        table.add_default_style();
+        sections.push(table.to_string());
+    }
+
+    sections.push(format!(
+        "{} none  {} 1  {} 2  {} 3  {} 4 or more",
+        heat(0),
+        heat(1),
+        heat(2),
+        heat(3),
+        heat(4)
+    ));
+
+    Ok(sections.join("\n\n"))
+}
+
+#[derive(Tabled)]
+struct SimpleSession {
+    day: String,
+    time: String,
+    subject: String,
+    test: String,
+    topics: String,
+}
+
+impl SimpleSession {
+    fn from_session(session: &Session) -> Self {
+        SimpleSession {
+            day: session.start.format("%Y-%m-%d %A").to_string(),
+            time: format!(
+                "{} - {}",
+                session.start.format("%H:%M"),
+                session.end.format("%H:%M")
+            ),
+            subject: session.subject.clone(),
+            test: session.test_date.format("%Y-%m-%d").to_string(),
+            topics: session.topics.join("; "),
+        }
+    }
+}
+
+// display the study plan as a table, as markdown or as an ICS calendar
+pub fn display_plan(plan: Plan, markdown: bool, ics: bool) -> String {
+    if ics {
+        return to_ics(&plan);
+    }
+    if plan.sessions.is_empty() && plan.unscheduled.is_empty() {
+        return String::from("No upcoming tests");
+    }
+
+    let unscheduled: Vec<String> = plan
+        .unscheduled
+        .iter()
+        .map(|(subject, date, sessions)| {
+            format!(
+                "{} sessions of {} for the test of {} do not fit in the free time",
+                sessions,
+                subject,
+                date.format("%Y-%m-%d")
+            )
+        })
+        .collect();
+
+    if markdown {
+        let mut lines: Vec<String> = vec!["# Study plan".to_string()];
+        let mut day = None;
+        for session in &plan.sessions {
+            if day != Some(session.start.date()) {
+                day = Some(session.start.date());
+                lines.push(format!("\n## {}\n", session.start.format("%Y-%m-%d %A")));
+            }
+            let mut line = format!(
+                "- {} - {} **{}** for the test of {}",
+                session.start.format("%H:%M"),
+                session.end.format("%H:%M"),
+                session.subject,
+                session.test_date.format("%Y-%m-%d")
+            );
+            if !session.topics.is_empty() {
+                line.push_str(&format!(
+                    ": {}",
+                    session.topics.join("; ").replace('\n', " ")
+                ));
+            }
+            lines.push(line);
+        }
+        if !unscheduled.is_empty() {
+            lines.push("\n## Not scheduled\n".to_string());
+            lines.extend(unscheduled.iter().map(|x| format!("- {}", x)));
+        }
+        return lines.join("\n");
+    }
+
+    let mut sections: Vec<String> = Vec::new();
+    if !plan.sessions.is_empty() {
+        let simplified_sessions: Vec<SimpleSession> = plan
+            .sessions
+            .iter()
+            .map(SimpleSession::from_session)
+            .collect();
+        let mut table = Table::new(simplified_sessions);
+        table.add_default_style();
+        sections.push(table.to_string());
+    }
+    sections.extend(unscheduled);
+
+    sections.join("\n")
+}
+
+#[derive(Tabled)]
+struct SimpleNote {
+    id: u32,
+    date: String,
+    category: String,
+    author: String,
+    text: String,
+    read: String,
+}
+
+impl SimpleNote {
+    fn from_note(kind: NoteKind, note: Note) -> Self {
+        let category = match note.warningType {
+            Some(warning) if !warning.is_empty() => format!("{} ({})", kind.label(), warning),
+            _ => kind.label().to_string(),
+        };
+        SimpleNote {
+            id: note.evtId,
+            date: note.evtDate,
+            category,
+            author: note.authorName,
+            text: note.evtText,
+            read: if note.readStatus { "yes" } else { "no" }.to_string(),
+        }
+    }
+}
+
+pub fn display_notes(notes: Vec<(NoteKind, Note)>, settings: Settings) -> String {
+    let mut simplified_notes: Vec<SimpleNote> = notes
+        .into_iter()
+        .map(|(kind, note)| SimpleNote::from_note(kind, note))
+        .collect();
+    if simplified_notes.is_empty() {
+        return String::from("No records");
+    }
+
+    if settings.desc_date {
+        simplified_notes.sort_by(|a, b| b.date.cmp(&a.date));
+    } else {
+        simplified_notes.sort_by(|a, b| a.date.cmp(&b.date));
+    }
+
+    let mut table = Table::new(simplified_notes);
+    table.add_default_style();
+
+    table.to_string()
+}
+
+// display the whole text of a note that has just been read
+pub fn display_note(kind: NoteKind, note: &Note, event: NoteEvent) -> String {
+    format!(
+        "{} - {} - {}\n\n{}",
+        note.evtDate,
+        kind.label(),
+        note.authorName,
+        event.evtText.trim()
+    )
+}
+
+#[derive(Tabled)]
+struct SimpleNotice {
+    id: u32,
+    date: String,
+    category: String,
+    title: String,
+    read: String,
+    attachments: String,
+}
+
+impl SimpleNotice {
+    fn from_notice(notice: Notice) -> Self {
+        let attachments: Vec<String> = notice.attachments.into_iter().map(|x| x.fileName).collect();
+        SimpleNotice {
+            id: notice.pubId,
+            date: notice.pubDT.chars().take(10).collect(),
+            category: notice.cntCategory,
+            title: notice.cntTitle,
+            read: if notice.readStatus { "yes" } else { "no" }.to_string(),
+            attachments: attachments.join("\n"),
+        }
+    }
+}
+
+pub fn display_noticeboard(notices: Vec<Notice>, settings: Settings) -> String {
+    let mut simplified_notices: Vec<SimpleNotice> =
+        notices.into_iter().map(SimpleNotice::from_notice).collect();
+    if simplified_notices.is_empty() {
+        return String::from("No records");
+    }
+
+    if settings.desc_date {
+        simplified_notices.sort_by(|a, b| b.date.cmp(&a.date));
+    } else {
+        simplified_notices.sort_by(|a, b| a.date.cmp(&b.date));
+    }
+
+    let mut table = Table::new(simplified_notices);
+    table.add_default_style();
+
+    table.to_string()
+}
+
+// display the text of a communication that has just been read
+pub fn display_notice(notice: &Notice, content: NoticeContent) -> String {
+    let title = content.title.unwrap_or_else(|| notice.cntTitle.clone());
+    let mut result = format!(
+        "{} - {}\n{}",
+        notice.pubDT.chars().take(10).collect::<String>(),
+        notice.cntCategory,
+        title.trim()
+    );
+    if let Some(text) = content.text.filter(|x| !x.trim().is_empty()) {
+        result.push_str(&format!("\n\n{}", text.trim()));
+    }
+    if !notice.attachments.is_empty() {
+        result.push_str("\n\nAttachments:");
+        for attachment in &notice.attachments {
+            result.push_str(&format!("\n  {}", attachment.fileName));
+        }
+    }
+    result
+}
+
+#[derive(Tabled)]
+struct SimpleMaterial {
+    id: u32,
+    teacher: String,
+    subject: String,
+    folder: String,
+    name: String,
+    kind: String,
+    shared: String,
+}
+
+impl SimpleMaterial {
+    fn from_material(material: Material) -> Self {
+        SimpleMaterial {
+            id: material.id,
+            teacher: material.teacher,
+            subject: material.subject.map(|x| x.name()).unwrap_or_default(),
+            folder: material.folder,
+            name: material.name,
+            kind: material.kind,
+            shared: material.shared,
+        }
+    }
+}
+
+// the didactic materials grouped by teacher and folder
+pub fn display_materials(materials: Vec<Material>, settings: Settings) -> String {
+    let mut simplified_materials: Vec<SimpleMaterial> = materials
+        .into_iter()
+        .map(SimpleMaterial::from_material)
+        .collect();
+    if simplified_materials.is_empty() {
+        return String::from("No records");
+    }
+
+    simplified_materials.sort_by(|a, b| {
+        let date = if settings.desc_date {
+            b.shared.cmp(&a.shared)
+        } else {
+            a.shared.cmp(&b.shared)
+        };
+        a.teacher
+            .cmp(&b.teacher)
+            .then(a.folder.cmp(&b.folder))
+            .then(date)
+    });
+
+    let mut table = Table::new(simplified_materials);
+    table.add_default_style();
+
+    table.to_string()
+}
+
+#[derive(Tabled)]
+struct SimpleDocument {
+    id: usize,
+    description: String,
+    available: String,
+    file: String,
+}
+
+// the documents with their availability, and the report cards only on the website
+pub fn display_documents(documents: Documents, available: Vec<bool>, year: i32) -> String {
+    if documents.documents.is_empty() && documents.schoolReports.is_empty() {
+        return String::from("No records");
+    }
+
+    let mut sections: Vec<String> = Vec::new();
+    let descs: Vec<&str> = documents.documents.iter().map(|x| &x.desc[..]).collect();
+    let simplified_documents: Vec<SimpleDocument> = documents
+        .documents
+        .iter()
+        .zip(file_names(&descs, year))
+        .zip(available)
+        .enumerate()
+        .map(|(index, ((document, file), available))| SimpleDocument {
+            id: index + 1,
+            description: document.desc.trim().to_string(),
+            available: if available { "yes" } else { "no" }.to_string(),
+            file,
+        })
+        .collect();
+    if !simplified_documents.is_empty() {
+        let mut table = Table::new(simplified_documents);
+        table.add_default_style();
+        sections.push(table.to_string());
+    }
+
+    if !documents.schoolReports.is_empty() {
+        let mut lines = vec!["Report cards on the website:".to_string()];
+        for report in &documents.schoolReports {
+            lines.push(format!(
+                "  {} {}",
+                report.desc.trim(),
+                report.viewLink.as_deref().unwrap_or_default()
+            ));
+        }
+        sections.push(lines.join("\n"));
+    }
+
+    sections.join("\n\n")
+}
+
+#[derive(Tabled)]
+struct SimpleSubject {
+    id: u32,
+    description: String,
+    name: String,
+    teachers: String,
+    hours: u32,
+    average: String,
+}
+
+/*
+Display the subjects of the class with their teachers, the hours of lesson so far and the current average
+the id can be used as key of the aliases in config.toml and with the --name option
+*/
+pub fn display_subjects(
+    subjects: Subjects,
+    lessons: Lessons,
+    grades: Grades,
+    name: &Option<String>,
+) -> Result<String, String> {
+    let subjects = filter_by_name(subjects.subjects, name, |x| {
+        Some(Subject::new(Some(x.id), None, &x.description))
+    })?;
+    if subjects.is_empty() {
+        return Ok(String::from("No records"));
+    }
+
+    let hours = hours_by_subject(&lessons.lessons);
+    let averages = current_averages(&grades.grades);
+    let mut subjects = subjects;
+    subjects.sort_by_key(|x| (x.order.unwrap_or(u32::MAX), x.id));
+
+    let simplified_subjects: Vec<SimpleSubject> = subjects
+        .into_iter()
+        .map(|subject| {
+            let teachers: Vec<&str> = subject
+                .teachers
+                .iter()
+                .map(|x| x.teacherName.trim())
+                .collect();
+            SimpleSubject {
+                id: subject.id,
+                name: subject_name(Some(subject.id), None, &subject.description),
+                description: subject.description.trim().to_string(),
+                teachers: teachers.join("\n"),
+                hours: hours.get(&subject.id).cloned().unwrap_or_default(),
+                average: averages
+                    .get(&subject.id)
+                    .map(|x| format!("{:.2}", x))
+                    .unwrap_or_else(|| "-".to_string()),
+            }
+        })
+        .collect();
+
+    let mut table = Table::new(simplified_subjects);
+    table.add_default_style();
+
+    Ok(table.to_string())
+}
+
+#[derive(Tabled)]
+struct SimpleBreak {
+    from: String,
+    to: String,
+    #[tabled(rename = "school days")]
+    school_days: usize,
+    status: String,
+}
+
+// the school days of the year and the periods without lessons
+pub fn display_calendar(calendar: &SchoolCalendar, today: NaiveDate) -> String {
+    if calendar.days.is_empty() {
+        return String::from("No records");
+    }
+
+    let school_days = calendar.days.values().filter(|x| *x == "SD").count();
+    let mut lines: Vec<String> = vec![format!("School days: {}", school_days)];
+    if let Some(last) = calendar.last_school_day() {
+        // today counts only while the school year is not over
+        let today_left = today <= last && calendar.is_school_day(today);
+        let left = calendar.school_days_between(today, last) + today_left as i64;
+        lines.push(format!(
+            "School days left: {} (last school day {})",
+            left.max(0),
+            last.format("%Y-%m-%d %A")
+        ));
+    }
+    let mut sections = vec![lines.join("\n")];
+
+    let simplified_breaks: Vec<SimpleBreak> = calendar
+        .breaks()
+        .into_iter()
+        .map(|(start, end, statuses)| {
+            let statuses: Vec<&str> = statuses.iter().map(|x| status_label(x)).collect();
+            SimpleBreak {
+                from: start.format("%Y-%m-%d %A").to_string(),
+                to: end.format("%Y-%m-%d %A").to_string(),
+                // the days lost, the weekends are not counted
+                school_days: start
+                    .iter_days()
+                    .take_while(|x| *x <= end)
+                    .filter(|x| has_lessons(*x))
+                    .count(),
+                status: statuses.join(", "),
+            }
+        })
+        .collect();
+    if !simplified_breaks.is_empty() {
+        let mut table = Table::new(simplified_breaks);
+        table.add_default_style();
+        sections.push(table.to_string());
+    }
+
+    sections.join("\n\n")
+}
+
+// readable label of the type of an account
+fn account_label(usr_type: &str) -> &str {
+    match usr_type {
+        "S" => "Studente",
+        "G" => "Genitore",
+        "T" => "Docente",
+        _ => usr_type,
+    }
+}
+
+// the profile of the current user, the class is taken from the lessons
+pub fn display_card(card: Card, class: Option<String>) -> String {
+    let mut school = card.schName.trim().to_string();
+    if let Some(dedication) = card.schDedication.filter(|x| !x.trim().is_empty()) {
+        school = format!("{} {}", school, dedication.trim());
+    }
+    let mut city = card.schCity.trim().to_string();
+    if let Some(province) = card.schProv.filter(|x| !x.trim().is_empty()) {
+        city = format!("{} ({})", city, province.trim());
+    }
+
+    let rows = [
+        ("name", format!("{} {}", card.firstName, card.lastName)),
+        ("class", class.unwrap_or_else(|| "-".to_string())),
+        ("school", school),
+        (
+            "school code",
+            card.miurSchoolCode.unwrap_or_else(|| card.schCode.clone()),
+        ),
+        ("city", city),
+        (
+            "account",
+            format!("{} ({})", account_label(&card.usrType), card.ident),
+        ),
+    ];
+
+    let mut builder = Builder::default();
+    for (key, value) in rows {
+        builder.push_record([key.to_string(), value]);
+    }
+    let mut table = builder.build();
+    table.with(Style::modern());
+
+    table.to_string()
+}
+
+// ANSI codes used to highlight the matches of a search
+const HIGHLIGHT_START: &str = "\x1b[1;33m";
+const HIGHLIGHT_END: &str = "\x1b[0m";
+
+/*
+Display the lessons, agenda events and grades matching a query, one per line
+the matches are highlighted only when the output is a terminal
+*/
+pub fn display_search(
+    query: &str,
+    lessons: Lessons,
+    agenda: Agendas,
+    grades: Grades,
+    name: &Option<String>,
+) -> Result<String, String> {
+    let grade_subject = |x: &Grade| {
+        Some(Subject::new(
+            Some(x.subjectId),
+            Some(&x.subjectCode),
+            &x.subjectDesc,
+        ))
+    };
+
+    // the same name must select the same subjects in every source
+    let subjects = lessons
+        .lessons
+        .iter()
+        .filter_map(Subject::from_lesson)
+        .chain(agenda.agenda.iter().filter_map(Subject::from_agenda))
+        .chain(grades.grades.iter().filter_map(grade_subject))
+        .collect::<Vec<Subject>>();
+    let selected = resolve_name(name, subjects)?;
+
+    let lessons = filter_by_subjects(lessons.lessons, &selected, Subject::from_lesson);
+    let agenda = filter_by_subjects(agenda.agenda, &selected, Subject::from_agenda);
+    let grades = filter_by_subjects(grades.grades, &selected, grade_subject);
+
+    let results = search(query, &lessons, &agenda, &grades);
+    if results.is_empty() {
+        return Ok(String::from("No records"));
+    }
+
+    let (start, end) = if std::io::stdout().is_terminal() {
+        (HIGHLIGHT_START, HIGHLIGHT_END)
+    } else {
+        ("", "")
+    };
+
+    Ok(results
+        .into_iter()
+        .map(|x| {
+            format!(
+                "{}  {:<6}  {}: {}",
+                x.date,
+                x.source,
+                x.subject,
+                highlight(&x.text.replace('\n', " "), &x.ranges, start, end)
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n"))
+}
+
+#[derive(Tabled)]
+struct MissedLesson {
+    hour: u32,
+    subject: String,
+    topic: String,
+    teacher: String,
+}
+
+#[derive(Tabled)]
+struct CatchupEvent {
+    date: String,
+    subject: String,
+    notes: String,
+}
+
+impl CatchupEvent {
+    fn from_agenda(agenda: Agenda) -> Self {
+        CatchupEvent {
+            date: agenda.evtDatetimeBegin.chars().take(10).collect(),
+            subject: Subject::from_agenda(&agenda)
+                .map(|x| x.name())
+                .unwrap_or(agenda.authorName),
+            notes: agenda.notes,
+        }
+    }
+}
+
+/*
+Display what has been missed during an absence period, both ends included
+the topics of the missed lessons for each day, the homework and the tests announced for the following days
+*/
+pub fn display_catchup(
+    period: (NaiveDate, NaiveDate),
+    absences: Absences,
+    lessons: Lessons,
+    agenda: Agendas,
+) -> String {
+    let (start, end) = period;
+    let mut sections: Vec<String> = vec![format!(
+        "Absence period: {} - {}",
+        start.format("%Y-%m-%d %A"),
+        end.format("%Y-%m-%d %A")
+    )];
+
+    let mut dates: Vec<&String> = absences.events.iter().map(|x| &x.evtDate).collect();
+    dates.sort();
+    dates.dedup();
+    for date in dates {
+        let day = match NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+            Ok(v) if v >= start && v <= end => v,
+            _ => continue,
+        };
+
+        let mut missed: Vec<&Lesson> = lessons
+            .lessons
+            .iter()
+            .filter(|lesson| absences.events.iter().any(|x| is_missed(x, lesson)))
+            .filter(|lesson| &lesson.evtDate == date)
+            .collect();
+        if missed.is_empty() {
+            continue;
+        }
+        missed.sort_by_key(|x| x.evtHPos);
+
+        let mut missed_lessons: Vec<MissedLesson> = Vec::new();
+        for lesson in missed {
+            let subject = Subject::from_lesson(lesson)
+                .map(|x| x.name())
+                .unwrap_or_default();
+            // the same lesson can be recorded by more than one teacher
+            if missed_lessons
+                .iter()
+                .any(|x| x.subject == subject && x.topic == lesson.lessonArg)
+            {
+                continue;
+            }
+            missed_lessons.push(MissedLesson {
+                hour: lesson.evtHPos,
+                subject,
+                topic: lesson.lessonArg.clone(),
+                teacher: lesson.authorName.clone(),
+            });
+        }
+
+        let mut table = Table::new(missed_lessons);
+        table.add_default_style();
+        sections.push(format!("{}\n{}", day.format("%Y-%m-%d %A"), table));
+    }
+
+    let mut agenda = agenda.agenda;
+    agenda.sort_by(|a, b| a.evtDatetimeBegin.cmp(&b.evtDatetimeBegin));
+
+    let (homework, others): (Vec<Agenda>, Vec<Agenda>) =
+        agenda.into_iter().partition(|x| x.evtCode == "AGHW");
+    let homework: Vec<CatchupEvent> = homework
+        .into_iter()
+        .map(CatchupEvent::from_agenda)
+        .collect();
+    let end_iso = end.format("%Y-%m-%d").to_string();
+    let tests: Vec<CatchupEvent> = others
+        .into_iter()
+        .filter(|x| {
+            x.evtDatetimeBegin
+                .get(..10)
+                .is_some_and(|date| date > &end_iso[..])
+                && is_assessment(&x.notes)
+        })
+        .map(CatchupEvent::from_agenda)
+        .collect();
+
+    for (title, events) in [("Homework", homework), ("Upcoming tests", tests)] {
+        if events.is_empty() {
+            sections.push(format!("{}: none", title));
+        } else {
+            let mut table = Table::new(events);
+            table.add_default_style();
+            sections.push(format!("{}\n{}", title, table));
+        }
+    }
+
+    sections.join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::response_types::fixtures::{absence, agenda, grade, lesson};
+    use serde_json::json;
+
+    #[test]
+    fn canceled_grades_are_listed_but_not_counted() {
+        let mut canceled = grade(1, "MATEMATICA", "2025-10-07", 2.0);
+        canceled.canceled = true;
+        let grades = Grades {
+            grades: vec![
+                grade(1, "MATEMATICA", "2025-10-06", 8.0),
+                canceled,
+                grade(2, "FISICA", "2025-10-08", 6.0),
+            ],
+        };
+        let settings = GradeSettings {
+            settings: Settings { desc_date: false },
+            name: None,
+        };
+
+        let (table, counted) = display_grades(grades, settings).unwrap();
+        assert!(table.contains("2025-10-07"));
+        let values: Vec<f64> = counted.iter().map(|x| x.grade).collect();
+        assert_eq!(values, vec![8.0, 6.0]);
+        assert!(!display_grade_stats(&counted).contains("2.00"));
+    }
+
+    #[test]
+    fn search_resolves_the_name_once_for_every_source() {
+        let search = |name: &str| {
+            display_search(
+                "derivate",
+                Lessons {
+                    lessons: vec![
+                        lesson("2025-10-06", 1, Some((1, "MATEMATICA")), "Derivate"),
+                        lesson("2025-10-07", 2, Some((2, "FISICA")), "Derivate del moto"),
+                    ],
+                },
+                Agendas { agenda: Vec::new() },
+                Grades {
+                    grades: vec![grade(2, "FISICA", "2025-10-08", 6.0)],
+                },
+                &Some(name.to_string()),
+            )
+        };
+
+        // the grades have no MATEMATICA, the name is still valid for the search
+        let result = search("matematica").unwrap();
+        assert!(!result.contains("Derivate del moto"));
+
+        assert_eq!(
+            search("chimica"),
+            Err("No subject matches \"chimica\"".to_string())
+        );
+    }
+
+    #[test]
+    fn absences_are_filtered_by_justification_kind_and_dates() {
+        let mut justified = absence("ABR0", "2025-10-07", Some(2), json!([]));
+        justified.isJustified = true;
+        let events = vec![
+            absence("ABA0", "2025-10-06", None, json!([])),
+            justified,
+            absence("ABR1", "2025-10-08", Some(1), json!([])),
+            absence("ABR0", "2025-10-20", Some(2), json!([])),
+        ];
+        let settings = AbsenceSettings {
+            settings: Settings { desc_date: false },
+            name: None,
+            unjustified: true,
+            kind: Some(AbsenceKind::Late),
+            from: None,
+            to: NaiveDate::from_ymd_opt(2025, 10, 10),
+        };
+
+        // both codes of the late entries, only the unjustified ones until the 10th
+        let events = filter_absences(events, None, &settings).unwrap();
+        let dates: Vec<&str> = events.iter().map(|x| x.evtDate.as_str()).collect();
+        assert_eq!(dates, vec!["2025-10-08"]);
+    }
+
+    #[test]
+    fn absence_reason_falls_back_to_the_label_of_the_code() {
+        let justified = |desc: &str| {
+            let mut event = absence("ABA0", "2025-10-06", None, json!([]));
+            event.isJustified = true;
+            event.justifReasonCode = Some("B".to_string());
+            event.justifReasonDesc = Some(desc.to_string());
+            SimpleAbsence::from_absence(event)
+        };
+
+        let simplified = justified(" ");
+        assert_eq!(simplified.kind, "Assenza");
+        assert_eq!(simplified.reason, "Motivi di famiglia");
+        assert_eq!(simplified.pending, "");
+
+        // the description of the server wins over the label
+        assert_eq!(justified("Visita medica").reason, "Visita medica");
+    }
+
+    #[test]
+    fn absences_are_filtered_by_the_subjects_of_the_missed_lessons() {
+        let lessons = Lessons {
+            lessons: vec![
+                lesson("2025-10-06", 1, Some((1, "MATEMATICA")), "Derivate"),
+                lesson("2025-10-07", 1, Some((2, "FISICA")), "Moto"),
+                lesson("2025-10-07", 6, Some((1, "MATEMATICA")), "Integrali"),
+            ],
+        };
+        let events = vec![
+            absence("ABR0", "2025-10-06", Some(2), json!([])),
+            // the lesson of MATEMATICA is after the entry
+            absence("ABR0", "2025-10-07", Some(2), json!([])),
+        ];
+        let settings = AbsenceSettings {
+            settings: Settings { desc_date: false },
+            name: Some("matematica".to_string()),
+            unjustified: false,
+            kind: None,
+            from: None,
+            to: None,
+        };
+
+        let events = filter_absences(events, Some(&lessons), &settings).unwrap();
+        let dates: Vec<&str> = events.iter().map(|x| x.evtDate.as_str()).collect();
+        assert_eq!(dates, vec!["2025-10-06"]);
+    }
+
+    #[test]
+    fn catchup_lists_the_missed_lessons_and_what_follows() {
+        let day = NaiveDate::from_ymd_opt(2025, 10, 6).unwrap();
+        let absences = Absences {
+            events: vec![absence("ABU0", "2025-10-06", Some(2), json!([]))],
+        };
+        let mut copresence = lesson("2025-10-06", 2, Some((1, "MATEMATICA")), "Derivate");
+        copresence.authorName = "BIANCHI ANNA".to_string();
+        let lessons = Lessons {
+            lessons: vec![
+                // before the exit
+                lesson("2025-10-06", 1, Some((2, "FISICA")), "Moto rettilineo"),
+                lesson("2025-10-06", 2, Some((1, "MATEMATICA")), "Derivate"),
+                copresence,
+                lesson("2025-10-07", 2, Some((1, "MATEMATICA")), "Integrali"),
+            ],
+        };
+        let agenda = Agendas {
+            agenda: vec![
+                agenda(1, "AGNT", "2025-10-09T08:00:00+02:00", "Verifica di fisica"),
+                agenda(2, "AGHW", "2025-10-08T08:00:00+02:00", "Esercizi pag. 12"),
+                agenda(3, "AGNT", "2025-10-10T08:00:00+02:00", "Uscita didattica"),
+                // during the absence, it has already taken place
+                agenda(4, "AGNT", "2025-10-06T08:00:00+02:00", "Verifica di storia"),
+            ],
+        };
+
+        let result = display_catchup((day, day), absences, lessons, agenda);
+        assert!(result.starts_with("Absence period: 2025-10-06 Monday - 2025-10-06 Monday"));
+        assert_eq!(result.matches("Derivate").count(), 1);
+        assert!(!result.contains("Moto rettilineo"));
+        assert!(!result.contains("Integrali"));
+        assert!(result.contains("Esercizi pag. 12"));
+        assert!(result.contains("Verifica di fisica"));
+        assert!(!result.contains("Uscita didattica"));
+        assert!(!result.contains("Verifica di storia"));
+    }
+
+    #[test]
+    fn lesson_grid_spans_the_longer_lessons() {
+        let mut physics = lesson("2025-10-06", 1, Some((2, "FISICA")), "Moto");
+        physics.evtDuration = 2;
+        let mut copresence = lesson("2025-10-06", 1, Some((2, "FISICA")), "Moto");
+        copresence.evtDuration = 2;
+        let lessons = Lessons {
+            lessons: vec![
+                lesson("2025-10-07", 3, Some((1, "MATEMATICA")), "Derivate"),
+                physics,
+                copresence,
+                // starts inside the span of FISICA
+                lesson("2025-10-06", 2, Some((3, "LABORATORIO")), "Sensori"),
+            ],
+        };
+        let settings = AgendaSettings {
+            settings: Settings { desc_date: false },
+            date: None,
+            name: None,
+        };
+
+        // one cell for the first two hours of monday, the copy of FISICA is shown once
+        let result = display_lesson_grid(lessons, settings).unwrap();
+        assert_eq!(
+            result,
+            [
+                "┌───┬─────────────┬─────────────┐",
+                "│   │  Mon 06/10  │  Tue 07/10  │",
+                "├───┼─────────────┼─────────────┤",
+                "│ 1 │ FISICA      │             │",
+                "│   │ ROSSI MARIO │             │",
+                "│   │ Moto        │             │",
+                "├───┼             ┼─────────────┤",
+                "│ 2 │ LABORATORIO │             │",
+                "│   │ ROSSI MARIO │             │",
+                "│   │ Sensori     │             │",
+                "├───┼─────────────┼─────────────┤",
+                "│ 3 │             │ MATEMATICA  │",
+                "│   │             │ ROSSI MARIO │",
+                "│   │             │ Derivate    │",
+                "└───┴─────────────┴─────────────┘",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn agenda_is_filtered_by_kind_and_teacher() {
+        let mut other_teacher =
+            agenda(3, "AGNT", "2025-10-08T08:00:00+02:00", "Verifica di storia");
+        other_teacher.authorName = "BIANCHI ANNA".to_string();
+        let events = || Agendas {
+            agenda: vec![
+                agenda(1, "AGNT", "2025-10-06T08:00:00+02:00", "Verifica di fisica"),
+                agenda(2, "AGHW", "2025-10-07T08:00:00+02:00", "Esercizi pag. 12"),
+                agenda(4, "AGCR", "2025-10-09T15:00:00+02:00", "Colloquio"),
+            ],
+        };
+        let settings = || AgendaSettings {
+            settings: Settings { desc_date: false },
+            date: None,
+            name: None,
+        };
+
+        let filter = AgendaFilter {
+            kind: Some(AgendaKind::Test),
+            teacher: Some("rossi".to_string()),
+        };
+        let mut tests = events();
+        tests.agenda.push(other_teacher);
+        let result = display_agenda(tests, settings(), filter).unwrap();
+        assert!(result.contains("Verifica di fisica"));
+        assert!(!result.contains("Verifica di storia"));
+        assert!(!result.contains("Esercizi"));
+
+        // events without a kind of their own show the label of the code
+        let filter = AgendaFilter {
+            kind: Some(AgendaKind::Event),
+            teacher: None,
+        };
+        let result = display_agenda(events(), settings(), filter).unwrap();
+        assert!(result.contains("Prenotazione"));
+        assert!(!result.contains("Verifica di fisica"));
+    }
+
+    #[test]
+    fn notice_falls_back_to_the_title_of_the_board() {
+        let notice: Notice = serde_json::from_value(json!({
+            "pubId": 1,
+            "pubDT": "2025-10-06T09:00:00+02:00",
+            "readStatus": false,
+            "evtCode": "CF",
+            "cntId": null,
+            "cntValidFrom": null,
+            "cntValidTo": null,
+            "cntValidInRange": null,
+            "cntStatus": null,
+            "cntTitle": "Circolare 12",
+            "cntCategory": "Circolari",
+            "cntHasChanged": null,
+            "cntHasAttach": true,
+            "needJoin": null,
+            "needReply": null,
+            "needFile": null,
+            "attachments": [{ "fileName": "circolare.pdf", "attachNum": 1 }],
+        }))
+        .unwrap();
+        let content = NoticeContent {
+            title: None,
+            text: Some(" \n".to_string()),
+        };
+
+        assert_eq!(
+            display_notice(&notice, content),
+            "2025-10-06 - Circolari\nCircolare 12\n\nAttachments:\n  circolare.pdf"
+        );
+    }
+
+    #[test]
+    fn subjects_are_listed_in_the_order_of_the_school() {
+        let subjects: Subjects = serde_json::from_value(json!({
+            "subjects": [
+                { "id": 2, "description": "FISICA", "order": 2, "teachers": [] },
+                {
+                    "id": 5,
+                    "description": "LINGUA E LETTERATURA ITALIANA",
+                    "order": 1,
+                    "teachers": [
+                        { "teacherId": null, "teacherName": " ROSSI MARIO" },
+                        { "teacherId": null, "teacherName": "BIANCHI ANNA" },
+                    ],
+                },
+            ],
+        }))
+        .unwrap();
+        let lessons = Lessons {
+            lessons: vec![lesson("2025-10-06", 1, Some((5, "ITALIANO")), "Dante")],
+        };
+        let grades = Grades {
+            grades: vec![grade(5, "ITALIANO", "2025-10-06", 7.5)],
+        };
+
+        // the name is the alias of config.example.toml, FISICA has neither hours nor grades
+        let result = display_subjects(subjects, lessons, grades, &None).unwrap();
+        let rows: Vec<&str> = result.lines().filter(|x| x.starts_with("│ ")).collect();
+        assert_eq!(
+            rows[1..],
+            [
+                "│ 5  │ LINGUA E LETTERATURA ITALIANA │ ITALIANO │ ROSSI MARIO  │ 1     │ 7.50    │",
+                "│    │                               │          │ BIANCHI ANNA │       │         │",
+                "│ 2  │ FISICA                        │ FISICA   │              │ 0     │ -       │",
+            ]
+        );
+    }
+
+    #[test]
+    fn card_skips_the_missing_fields() {
+        let response = json!({
+            "card": {
+                "ident": "S1234567X",
+                "usrType": "S",
+                "usrId": 1234567,
+                "miurSchoolCode": null,
+                "miurDivisionCode": null,
+                "firstName": "MARIO",
+                "lastName": "ROSSI",
+                "birthDate": "2008-05-04",
+                "fiscalCode": null,
+                "schCode": "XX0001",
+                "schName": "ISTITUTO TECNICO ",
+                "schDedication": " ",
+                "schCity": "TORINO",
+                "schProv": "TO",
+            },
+        });
+        let card = match serde_json::from_value(response).unwrap() {
+            ResponseResult::Card(v) => v.card,
+            _ => panic!("not parsed as a card"),
+        };
+
+        let result = display_card(card, None);
+        let rows: Vec<&str> = result.lines().filter(|x| x.starts_with("│ ")).collect();
+        assert_eq!(
+            rows,
+            [
+                "│ name        │ MARIO ROSSI          │",
+                "│ class       │ -                    │",
+                "│ school      │ ISTITUTO TECNICO     │",
+                "│ school code │ XX0001               │",
+                "│ city        │ TORINO (TO)          │",
+                "│ account     │ Studente (S1234567X) │",
+            ]
+        );
+    }
+}
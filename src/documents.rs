@@ -0,0 +1,157 @@
+// Module: documents
+use crate::api::get_school_year_start;
+use crate::subjects::normalize;
+
+// the period of a report card from its description, e.g. "Pagella primo quadrimestre" -> "1Q"
+fn period(desc: &str) -> Option<String> {
+    let words: Vec<&str> = desc
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|x| !x.is_empty())
+        .collect();
+
+    // the kind of period and the number written right before it, e.g. "primo quadrimestre"
+    let kind = words.iter().position(|word| {
+        let word = word.to_lowercase();
+        ["quadrimestr", "trimestr", "pentamestr"]
+            .iter()
+            .any(|x| word.starts_with(x))
+    });
+    let number = kind
+        .and_then(|index| index.checked_sub(1))
+        .and_then(|index| match &words[index].to_lowercase()[..] {
+            "primo" | "1" | "i" => Some("1"),
+            "secondo" | "2" | "ii" => Some("2"),
+            "terzo" | "3" | "iii" => Some("3"),
+            _ => None,
+        });
+    let kind = kind.map(|index| match words[index].to_lowercase().chars().next() {
+        Some('q') => "Q",
+        Some('t') => "T",
+        _ => "P",
+    });
+
+    match (number, kind) {
+        (Some(number), Some(kind)) => Some(format!("{}{}", number, kind)),
+        // the end of year report card
+        _ if words.iter().any(|x| normalize(x) == "finale") => Some("F".to_string()),
+        _ => None,
+    }
+}
+
+/*
+The year a document belongs to from its description, e.g. "Pagella a.s. 2024/2025" -> 2024
+in a school year written as a range the first year is the one the school year starts in
+*/
+fn desc_year(desc: &str) -> Option<i32> {
+    desc.split(|c: char| !c.is_ascii_digit())
+        .filter(|x| x.len() == 4)
+        .filter_map(|x| x.parse().ok())
+        .find(|x| (1990..=2100).contains(x))
+}
+
+/*
+Name of the file of a document, the year is the one the school year starts in
+taken from the description, the given year is used when the description has none
+report cards are named after their period, e.g. pagella-2025-1Q.pdf
+the other documents after their description, e.g. certificato-di-iscrizione-2025.pdf
+*/
+pub fn file_name(desc: &str, year: i32) -> String {
+    let year = desc_year(desc).unwrap_or(year);
+    let normalized = normalize(desc);
+    let is_report_card = normalized.contains("pagella") || normalized.contains("scrutinio");
+    if let (true, Some(period)) = (is_report_card, period(desc)) {
+        return format!("pagella-{}-{}.pdf", year, period);
+    }
+
+    let slug: Vec<String> = normalized
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|x| !x.is_empty())
+        .map(|x| x.to_string())
+        .collect();
+    let slug = match slug.is_empty() {
+        true => "documento".to_string(),
+        false => slug.join("-"),
+    };
+    format!("{}-{}.pdf", slug, year)
+}
+
+// names of the files of the documents, the same name gets a number, e.g. pagella-2025-1Q-2.pdf
+pub fn file_names(descs: &[&str], year: i32) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for desc in descs {
+        let name = file_name(desc, year);
+        let mut unique = name.clone();
+        let mut count = 1;
+        while names.contains(&unique) {
+            count += 1;
+            unique = format!("{}-{}.pdf", name.trim_end_matches(".pdf"), count);
+        }
+        names.push(unique);
+    }
+    names
+}
+
+// the year the current school year starts in, used for the documents without a year
+pub fn school_year() -> i32 {
+    get_school_year_start()[..4]
+        .parse()
+        .expect("Invalid date format")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn period_of_report_cards() {
+        assert_eq!(period("Pagella primo quadrimestre").as_deref(), Some("1Q"));
+        assert_eq!(period("Esito scrutinio 2 trimestre").as_deref(), Some("2T"));
+        assert_eq!(period("Pagella II pentamestre").as_deref(), Some("2P"));
+        assert_eq!(period("Pagella scrutinio finale").as_deref(), Some("F"));
+        assert_eq!(period("Certificato di iscrizione"), None);
+        // only the number right before the period counts
+        assert_eq!(
+            period("Pagella classe 2 B primo quadrimestre").as_deref(),
+            Some("1Q")
+        );
+        assert_eq!(
+            period("Pagella con i voti del secondo quadrimestre").as_deref(),
+            Some("2Q")
+        );
+        assert_eq!(period("Pagella 2 del quadrimestre"), None);
+    }
+
+    #[test]
+    fn file_names_take_the_year_of_the_document() {
+        let descs = [
+            "Pagella primo quadrimestre",
+            "Pagella primo quadrimestre a.s. 2023/2024",
+            "Certificato di iscrizione",
+        ];
+        assert_eq!(
+            file_names(&descs, 2025),
+            vec![
+                "pagella-2025-1Q.pdf",
+                "pagella-2023-1Q.pdf",
+                "certificato-di-iscrizione-2025.pdf",
+            ]
+        );
+    }
+
+    #[test]
+    fn file_names_are_unique() {
+        let descs = [
+            "Pagella primo quadrimestre",
+            "Pagella 1 quadrimestre",
+            "???",
+        ];
+        assert_eq!(
+            file_names(&descs, 2025),
+            vec![
+                "pagella-2025-1Q.pdf",
+                "pagella-2025-1Q-2.pdf",
+                "documento-2025.pdf",
+            ]
+        );
+    }
+}
@@ -0,0 +1,83 @@
+// Module: downloads
+use std::path::{Path, PathBuf};
+
+// keep only the name of a file sent by the server, so that it cannot be written elsewhere
+pub fn safe_file_name(name: &str) -> String {
+    let name = name
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+    match name.as_str() {
+        "" | "." | ".." => "file".to_string(),
+        _ => name,
+    }
+}
+
+/*
+Write a downloaded file, creating its directory if needed
+written to a temporary file next to it first, so that an interrupted write leaves no partial file
+*/
+pub fn save(path: &Path, bytes: &[u8]) {
+    if let Some(dir) = path.parent() {
+        match std::fs::create_dir_all(dir) {
+            Ok(_) => (),
+            Err(e) => panic!("error at creating the directory {}: {}", dir.display(), e),
+        };
+    }
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+    match std::fs::write(&temp_path, bytes) {
+        Ok(_) => (),
+        Err(e) => panic!("error at writing the file {}: {}", temp_path.display(), e),
+    };
+    if let Err(e) = std::fs::rename(&temp_path, path) {
+        panic!("error at replacing the file {}: {}", path.display(), e);
+    }
+}
+
+// the directory given with --dir, the current directory by default
+pub fn target_dir(dir: Option<String>) -> PathBuf {
+    match dir {
+        Some(v) => PathBuf::from(v),
+        None => PathBuf::from("."),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_names_stay_in_the_directory() {
+        assert_eq!(safe_file_name("circolare 12.pdf"), "circolare 12.pdf");
+        assert_eq!(safe_file_name("../../.bashrc"), ".bashrc");
+        assert_eq!(safe_file_name("/etc/passwd"), "passwd");
+        assert_eq!(
+            safe_file_name("C:\\Users\\prof\\compiti.docx"),
+            "compiti.docx"
+        );
+        assert_eq!(safe_file_name(" .. "), "file");
+        assert_eq!(safe_file_name("cartella/"), "file");
+        assert_eq!(safe_file_name(""), "file");
+    }
+
+    #[test]
+    fn save_replaces_the_file_without_leftovers() {
+        let dir = std::env::temp_dir().join(format!("terminalviva-save-{}", std::process::id()));
+        let path = dir.join("docs").join("esercizi.pdf");
+
+        save(&path, b"first");
+        save(&path, b"second");
+        assert_eq!(std::fs::read(&path).unwrap(), b"second");
+        let files: Vec<_> = std::fs::read_dir(path.parent().unwrap())
+            .unwrap()
+            .map(|x| x.unwrap().file_name())
+            .collect();
+        assert_eq!(files, vec!["esercizi.pdf"]);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,148 @@
+// Module: homework
+use crate::response_types::{Agenda, Lesson};
+use crate::subjects::{agenda_subject, subjects_by_teacher, Subject};
+use crate::USER_CONFIG;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+const HOMEWORK_FILE: &str = "homework.json";
+
+// days of the agenda looked up before and after today by the homework command
+pub const PAST_DAYS: i64 = 30;
+pub const NEXT_DAYS: i64 = 30;
+
+// homework marked as done, matched by the evtId of the agenda
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct HomeworkState {
+    pub done: BTreeSet<u32>,
+}
+
+impl HomeworkState {
+    /*
+    Load the homework marked as done, empty if the file has never been stored
+    a broken file is not replaced, so that the ticks are never lost
+    */
+    pub fn load() -> Self {
+        let file = match std::fs::File::open(USER_CONFIG.data_path(HOMEWORK_FILE)) {
+            Ok(v) => v,
+            Err(_) => return HomeworkState::default(),
+        };
+        match serde_json::from_reader(file) {
+            Ok(v) => v,
+            Err(e) => panic!("error at reading the homework file: {}", e),
+        }
+    }
+
+    // written to a temporary file first, so that an interrupted write never truncates the ticks
+    pub fn store(&self) {
+        let path = USER_CONFIG.data_path(HOMEWORK_FILE);
+        let temp_path = path.with_extension("json.tmp");
+        let file = match std::fs::File::create(&temp_path) {
+            Ok(v) => v,
+            Err(e) => panic!("error at creating the homework file: {}", e),
+        };
+        match serde_json::to_writer(file, self) {
+            Ok(_) => (),
+            Err(e) => panic!("error at writing the homework file: {}", e),
+        };
+        if let Err(e) = std::fs::rename(&temp_path, &path) {
+            panic!("error at replacing the homework file: {}", e);
+        }
+    }
+}
+
+pub struct Homework {
+    pub id: u32,
+    pub due: NaiveDate,
+    pub subject: Option<Subject>,
+    pub notes: String,
+    pub teacher: String,
+    pub done: bool,
+}
+
+impl Homework {
+    pub fn is_overdue(&self, today: NaiveDate) -> bool {
+        !self.done && self.due < today
+    }
+}
+
+// the homework of the agenda, the ones without a subject get the subject taught by their author
+pub fn collect(agenda: &[Agenda], lessons: &[Lesson], state: &HomeworkState) -> Vec<Homework> {
+    let teachers = subjects_by_teacher(lessons);
+    let mut homework: Vec<Homework> = agenda
+        .iter()
+        .filter(|x| x.evtCode == "AGHW")
+        .filter_map(|event| {
+            let due = event.evtDatetimeBegin.get(..10)?.parse().ok()?;
+            Some(Homework {
+                id: event.evtId,
+                due,
+                subject: agenda_subject(event, &teachers),
+                notes: event.notes.trim().to_string(),
+                teacher: event.authorName.clone(),
+                done: state.done.contains(&event.evtId),
+            })
+        })
+        .collect();
+
+    homework.sort_by_key(|x| (x.due, x.id));
+    homework
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::response_types::fixtures::{agenda, lesson};
+
+    #[test]
+    fn state_round_trip() {
+        let state = HomeworkState {
+            done: BTreeSet::from([7, 3]),
+        };
+        let json = serde_json::to_string(&state).unwrap();
+        assert_eq!(json, r#"{"done":[3,7]}"#);
+        let state: HomeworkState = serde_json::from_str(&json).unwrap();
+        assert_eq!(state.done, BTreeSet::from([3, 7]));
+    }
+
+    #[test]
+    fn collect_marks_the_done_homework() {
+        let events = vec![
+            agenda(3, "AGHW", "2025-10-09T08:00:00+02:00", " Esercizi pag. 12 "),
+            agenda(
+                2,
+                "AGHW",
+                "2025-10-07T08:00:00+02:00",
+                "Leggere il capitolo 2",
+            ),
+            agenda(4, "AGNT", "2025-10-08T08:00:00+02:00", "Uscita didattica"),
+            agenda(5, "AGHW", "", "Senza data"),
+        ];
+        // the only subject of ROSSI MARIO
+        let lessons = vec![lesson("2025-10-06", 1, Some((2, "FISICA")), "Moto")];
+        let state = HomeworkState {
+            done: BTreeSet::from([3]),
+        };
+
+        let homework = collect(&events, &lessons, &state);
+        let found: Vec<(u32, &str, bool)> = homework
+            .iter()
+            .map(|x| (x.id, x.notes.as_str(), x.done))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (2, "Leggere il capitolo 2", false),
+                (3, "Esercizi pag. 12", true),
+            ]
+        );
+        assert_eq!(homework[0].subject.as_ref().unwrap().desc, "FISICA");
+
+        let today = NaiveDate::from_ymd_opt(2025, 10, 10).unwrap();
+        // the done homework is never overdue
+        assert!(homework[0].is_overdue(today));
+        assert!(!homework[1].is_overdue(today));
+        assert!(!homework[0].is_overdue(homework[0].due));
+    }
+}
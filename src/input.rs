@@ -1,5 +1,21 @@
+use crate::absences::{absence_periods, AbsenceKind, CATCHUP_DAYS};
+use crate::agenda::AgendaKind;
 use crate::api;
+use crate::assessments::{detect, Assessment};
 use crate::display;
+use crate::documents::{file_names, school_year};
+use crate::downloads::{safe_file_name, save, target_dir};
+use crate::homework::{collect, HomeworkState, NEXT_DAYS, PAST_DAYS};
+use crate::materials::{download, materials};
+use crate::notes::{all_notes, NoteKind};
+use crate::planner::{self, subject_averages};
+use crate::risk::Severity;
+use crate::stats;
+use crate::subjects::{filter_by_name, normalize, subjects_by_teacher};
+use crate::timetable::{inference_range, Timetable};
+use crate::workload::{workload, DEFAULT_WEEKS};
+use crate::USER_CONFIG;
+use chrono::{offset::Local, Datelike, Duration, NaiveDate};
 use clap::{Parser, Subcommand};
 
 #[derive(Parser, Debug)]
@@ -29,18 +45,239 @@ struct Args {
 #[derive(Subcommand, Debug)]
 enum Commands {
     #[clap(name = "grade", about = "Display grades of the current user")]
-    Grade,
+    Grade {
+        #[arg(
+            long,
+            help = "Display statistics and the distribution of the grades",
+            default_value = "false"
+        )]
+        stats: bool,
+    },
+    #[clap(
+        name = "risk",
+        about = "Display the subjects at risk, exit code is 2 for warnings and 3 for subjects at risk"
+    )]
+    Risk,
+    #[clap(
+        name = "credit",
+        about = "Display the estimated credito scolastico from the current grades"
+    )]
+    Credit,
     #[clap(name = "lesson", about = "Display current week lessons")]
-    Lesson,
+    Lesson {
+        #[arg(
+            long,
+            help = "Display the lessons as a week calendar",
+            default_value = "false"
+        )]
+        grid: bool,
+    },
     #[clap(name = "absence", about = "Display absences of the current user")]
-    Absence,
+    Absence {
+        #[arg(
+            long,
+            help = "Display the missed hours against the attendance limit",
+            default_value = "false"
+        )]
+        summary: bool,
+        #[arg(
+            long,
+            help = "Display the hours missed for each subject",
+            default_value = "false"
+        )]
+        by_subject: bool,
+        #[arg(
+            long,
+            help = "Display only the events to be justified",
+            default_value = "false"
+        )]
+        unjustified: bool,
+        #[arg(long = "type", help = "Display only one kind of events")]
+        kind: Option<AbsenceKind>,
+        #[arg(long, help = "Display the events from this date(ISO 8601, YYYY-MM-DD)")]
+        from: Option<String>,
+        #[arg(
+            long,
+            help = "Display the events until this date(ISO 8601, YYYY-MM-DD)"
+        )]
+        to: Option<String>,
+    },
+    #[clap(
+        name = "catchup",
+        about = "Display what has been missed during the last absence, or the one of --date"
+    )]
+    Catchup,
+    #[clap(
+        name = "timetable",
+        about = "Display the weekly timetable inferred from the last weeks of lessons"
+    )]
+    Timetable {
+        #[arg(
+            long,
+            help = "Infer the timetable again from the lessons",
+            default_value = "false"
+        )]
+        update: bool,
+    },
+    #[clap(name = "now", about = "Display the lesson taking place now")]
+    Now,
+    #[clap(
+        name = "next",
+        about = "Display the next lesson and the subjects of its day"
+    )]
+    Next,
+    #[clap(
+        name = "syllabus",
+        about = "Display the topics covered for each subject during the school year"
+    )]
+    Syllabus {
+        #[arg(long, help = "Display the topics from this date(ISO 8601, YYYY-MM-DD)")]
+        from: Option<String>,
+        #[arg(
+            long,
+            help = "Display the topics until this date(ISO 8601, YYYY-MM-DD)"
+        )]
+        to: Option<String>,
+        #[arg(long, help = "Display the topics as markdown", default_value = "false")]
+        markdown: bool,
+    },
+    #[clap(
+        name = "exams",
+        about = "Display the upcoming tests and the topics covered since the previous ones"
+    )]
+    Exams,
+    #[clap(
+        name = "homework",
+        about = "Display the homework to do grouped by subject, by due date"
+    )]
+    Homework {
+        #[command(subcommand)]
+        action: Option<HomeworkAction>,
+        #[arg(
+            long,
+            help = "Display the homework marked as done as well",
+            default_value = "false"
+        )]
+        all: bool,
+        #[arg(
+            long,
+            help = "Display only the homework past its due date",
+            default_value = "false"
+        )]
+        overdue: bool,
+    },
+    #[clap(
+        name = "workload",
+        about = "Display the homework and the tests of the coming weeks as a calendar"
+    )]
+    Workload {
+        #[arg(long, help = "Weeks to display, starting from the current one")]
+        weeks: Option<u32>,
+    },
+    #[clap(
+        name = "plan",
+        about = "Display a revision schedule for the upcoming tests in the free time"
+    )]
+    Plan {
+        #[arg(long, help = "Display the plan as markdown", default_value = "false")]
+        markdown: bool,
+        #[arg(
+            long,
+            help = "Display the plan as an ICS calendar",
+            default_value = "false"
+        )]
+        ics: bool,
+    },
+    #[clap(name = "notes", about = "Display the notes of the teachers")]
+    Notes {
+        #[command(subcommand)]
+        action: Option<NoteAction>,
+        #[arg(
+            long,
+            help = "Display only the notes not read yet",
+            default_value = "false"
+        )]
+        unread: bool,
+        #[arg(long = "type", help = "Display only one category of notes")]
+        kind: Option<NoteKind>,
+    },
+    #[clap(
+        name = "board",
+        about = "Display the communications of the noticeboard"
+    )]
+    Board {
+        #[command(subcommand)]
+        action: Option<BoardAction>,
+        #[arg(
+            long,
+            help = "Display only the communications not read yet",
+            default_value = "false"
+        )]
+        unread: bool,
+        #[arg(long, help = "Display only the communications of a category")]
+        category: Option<String>,
+    },
+    #[clap(
+        name = "materials",
+        about = "Display the didactic materials shared by the teachers"
+    )]
+    Materials {
+        #[command(subcommand)]
+        action: Option<MaterialAction>,
+    },
+    #[clap(
+        name = "documents",
+        about = "Display the documents and the report cards of the current user"
+    )]
+    Documents {
+        #[command(subcommand)]
+        action: Option<DocumentAction>,
+    },
+    #[clap(
+        name = "subjects",
+        about = "Display the subjects with their id, teachers, hours of lesson and average"
+    )]
+    Subjects,
+    #[clap(
+        name = "calendar",
+        about = "Display the school days, the holidays and the closures of the school year"
+    )]
+    Calendar {
+        #[arg(
+            long,
+            help = "Fetch the calendar again instead of using the stored one",
+            default_value = "false"
+        )]
+        update: bool,
+    },
+    #[clap(name = "me", about = "Display the profile of the current user")]
+    Me,
+    #[clap(
+        name = "search",
+        about = "Search the lessons, the agenda and the notes of the grades of the school year"
+    )]
+    Search {
+        query: String,
+        #[arg(long, help = "Search from this date(ISO 8601, YYYY-MM-DD)")]
+        from: Option<String>,
+        #[arg(
+            long,
+            help = "Search until this date(ISO 8601, YYYY-MM-DD), today by default"
+        )]
+        to: Option<String>,
+    },
     #[clap(name = "login", about = "Login to spaggiari")]
     Login,
     #[clap(
         name = "agenda",
         about = "Display agenda of the current user, default is the current day"
     )]
-    Agenda,
+    Agenda {
+        #[arg(long = "type", help = "Display only one kind of events")]
+        kind: Option<AgendaKind>,
+        #[arg(long, help = "Display only the events of a teacher")]
+        teacher: Option<String>,
+    },
 }
 
 pub struct Settings {
@@ -63,30 +300,174 @@ impl GradeSettings {
     }
 }
 
+// validate an iso 8601 string
+fn parse_date(date: &str) -> NaiveDate {
+    match NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+        Ok(date) => date,
+        Err(_) => {
+            panic!("Invalid date format, please follow ISO 8601 standard format(YYYY-MM-DD)");
+        }
+    }
+}
+
+// stop with the error of a command, e.g. a --name matching no subject
+fn or_exit<T>(result: Result<T, String>) -> T {
+    match result {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+pub struct AbsenceSettings {
+    pub settings: Settings,
+    pub name: Option<String>,
+    pub unjustified: bool,
+    pub kind: Option<AbsenceKind>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+impl AbsenceSettings {
+    fn new(
+        settings: Settings,
+        name: Option<String>,
+        unjustified: bool,
+        kind: Option<AbsenceKind>,
+        from: Option<String>,
+        to: Option<String>,
+    ) -> Self {
+        AbsenceSettings {
+            settings,
+            name,
+            unjustified,
+            kind,
+            from: from.as_deref().map(parse_date),
+            to: to.as_deref().map(parse_date),
+        }
+    }
+}
+
 pub struct AgendaSettings {
     pub settings: Settings,
     pub date: Option<String>,
+    pub name: Option<String>,
 }
 
 impl AgendaSettings {
-    fn new(settings: Settings, date: Option<String>) -> Self {
+    fn new(settings: Settings, date: Option<String>, name: Option<String>) -> Self {
         let date = match date {
             Some(date) => {
                 if date == "nextweek" {
                     Some(date)
                 } else {
-                    // validate an iso 8601 string
-                    match chrono::NaiveDate::parse_from_str(&date, "%Y-%m-%d") {
-                        Ok(date) => Some(date.to_string().replace("-", "")),
-                        Err(_) => {
-                            panic!("Invalid date format, please follow ISO 8601 standard format(YYYY-MM-DD)");
-                        }
-                    }
+                    Some(parse_date(&date).format("%Y%m%d").to_string())
                 }
             }
             None => None,
         };
-        AgendaSettings { settings, date }
+        AgendaSettings {
+            settings,
+            date,
+            name,
+        }
+    }
+}
+
+// load the stored timetable, it is inferred from the lessons the first time or when updating
+async fn get_timetable(update: bool) -> Timetable {
+    let stored = if update { None } else { Timetable::load() };
+    let timetable = match stored {
+        Some(v) => v,
+        None => {
+            let (start, end) = inference_range(Local::now().date_naive());
+            let lessons = api::lessons_range_request(
+                &start.format("%Y%m%d").to_string(),
+                &end.format("%Y%m%d").to_string(),
+            )
+            .await;
+            let timetable = Timetable::from_lessons(&lessons.lessons);
+            timetable.store();
+            timetable
+        }
+    };
+
+    timetable.with_overrides()
+}
+
+#[derive(Subcommand, Debug)]
+enum HomeworkAction {
+    #[clap(name = "done", about = "Mark a homework as done")]
+    Done { id: u32 },
+    #[clap(name = "undo", about = "Mark a homework as not done")]
+    Undo { id: u32 },
+}
+
+#[derive(Subcommand, Debug)]
+enum NoteAction {
+    #[clap(name = "read", about = "Read a note, marking it as read")]
+    Read { id: u32 },
+}
+
+#[derive(Subcommand, Debug)]
+enum BoardAction {
+    #[clap(name = "read", about = "Read a communication, marking it as read")]
+    Read { id: u32 },
+    #[clap(
+        name = "download",
+        about = "Download the attachments of a communication"
+    )]
+    Download {
+        id: u32,
+        #[arg(
+            long,
+            help = "Directory of the attachments, the current one by default"
+        )]
+        dir: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum MaterialAction {
+    #[clap(name = "download", about = "Download a file of the didactic materials")]
+    Download {
+        id: u32,
+        #[arg(long, help = "Directory of the file, the current one by default")]
+        dir: Option<String>,
+    },
+    #[clap(
+        name = "mirror",
+        about = "Download every material into teacher/folder/file, skipping the existing files"
+    )]
+    Mirror {
+        #[arg(long, help = "Directory of the mirror, the current one by default")]
+        dir: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum DocumentAction {
+    #[clap(
+        name = "download",
+        about = "Download a document, or every available document without an id"
+    )]
+    Download {
+        id: Option<usize>,
+        #[arg(long, help = "Directory of the documents, the current one by default")]
+        dir: Option<String>,
+    },
+}
+
+pub struct AgendaFilter {
+    pub kind: Option<AgendaKind>,
+    pub teacher: Option<String>,
+}
+
+impl AgendaFilter {
+    fn new(kind: Option<AgendaKind>, teacher: Option<String>) -> Self {
+        AgendaFilter { kind, teacher }
     }
 }
 
@@ -95,46 +476,557 @@ pub async fn process_input() {
 
     let settings = Settings::new(args.desc_date);
 
+    /*
+    the name of the profile labels the output when more profiles are used
+    printed to stderr, so that the exported calendars and markdown stay untouched
+    */
+    if USER_CONFIG.user_settings.profile_label
+        && !matches!(args.command, Commands::Login | Commands::Me)
+    {
+        let card = api::profile_card(false).await;
+        eprintln!("{} {}\n", card.firstName, card.lastName);
+    }
+
     match args.command {
         Commands::Login => {
             api::login().await;
         }
-        Commands::Absence => {
-            let result = api::absences_request().await;
-            let result = display::display_absences(result);
+        Commands::Absence {
+            summary,
+            by_subject,
+            unjustified,
+            kind,
+            from,
+            to,
+        } => {
+            let absence_settings =
+                AbsenceSettings::new(settings, args.name, unjustified, kind, from, to);
+            let mut result = api::absences_request().await;
+
+            // absences are not related to a subject, the lessons of those days tell what has been missed
+            let first_date = result.events.iter().map(|x| &x.evtDate).min();
+            let last_date = result.events.iter().map(|x| &x.evtDate).max();
+            let lessons = match (by_subject, &absence_settings.name, first_date, last_date) {
+                // the whole school year is needed to know the hours held for each subject
+                (true, _, _, _) => Some(
+                    api::lessons_range_request(&api::get_school_year_start(), &api::get_today())
+                        .await,
+                ),
+                (false, Some(_), Some(first), Some(last)) => Some(
+                    api::lessons_range_request(&first.replace('-', ""), &last.replace('-', ""))
+                        .await,
+                ),
+                _ => None,
+            };
+
+            // every view shows only the absences matching the filters
+            result.events = or_exit(display::filter_absences(
+                result.events,
+                lessons.as_ref(),
+                &absence_settings,
+            ));
+
+            if summary {
+                println!("{}", display::display_absence_summary(result));
+                return;
+            }
+
+            if let (true, Some(lessons)) = (by_subject, lessons) {
+                let result = or_exit(display::display_absences_by_subject(
+                    result,
+                    lessons,
+                    absence_settings,
+                ));
+                println!("{}", result);
+                return;
+            }
+
+            let result = display::display_absences(result, absence_settings);
             println!("{}", result);
         }
-        Commands::Grade => {
+        Commands::Grade { stats } => {
             let grade_settings = GradeSettings::new(settings, args.name);
             let result = api::grades_request().await;
-            let (result, grades) = display::display_grades(result, grade_settings);
+            let (result, grades) = or_exit(display::display_grades(result, grade_settings));
+
+            if stats {
+                println!("{}", display::display_grade_stats(&grades));
+                return;
+            }
 
             // average
-            let average = grades.iter().fold(0.0, |a, b| a + b.grade) / (grades.len() as f64);
+            let values: Vec<f64> = grades.iter().map(|x| x.grade).collect();
+            let average = stats::average(&values);
 
             // weighted average
-            let mut sum: f64 = 0.0;
-            let mut weights: f64 = 0.0;
-            for grade in grades {
-                sum += grade.grade * grade.weight;
-                weights += grade.weight;
-            }
-            let weighted_average = sum / weights;
+            let weighted_values: Vec<(f64, f64)> =
+                grades.iter().map(|x| (x.grade, x.weight)).collect();
+            let weighted_average = stats::weighted_average(&weighted_values);
 
             println!("{}", result);
             println!("The average grade is {:.2}.", average);
             println!("The weighted average grade is {:.2}.", weighted_average);
         }
-        Commands::Agenda => {
-            let agenda_settings = AgendaSettings::new(settings, args.date);
-            let result = api::agenda_request(agenda_settings.date).await;
-            let result = display::display_agenda(result);
+        Commands::Risk => {
+            let grade_settings = GradeSettings::new(settings, args.name);
+            let result = api::grades_request().await;
+            let (result, severity) = or_exit(display::display_risks(result, grade_settings));
+            println!("{}", result);
+
+            // exit codes usable in scripts
+            match severity {
+                Some(Severity::High) => std::process::exit(3),
+                Some(_) => std::process::exit(2),
+                None => (),
+            }
+        }
+        Commands::Credit => {
+            let result = api::grades_request().await;
+            let result = display::display_credit(result);
+            println!("{}", result);
+        }
+        Commands::Catchup => {
+            let absences = api::absences_request().await;
+            let periods = absence_periods(&absences.events);
+
+            // the period containing the selected date or the last one before it
+            let period = match args.date {
+                Some(date) => {
+                    let date = parse_date(&date);
+                    periods.into_iter().rfind(|(start, _)| *start <= date)
+                }
+                None => periods.into_iter().next_back(),
+            };
+            let (start, end) = match period {
+                Some(v) => v,
+                None => {
+                    println!("No absences");
+                    return;
+                }
+            };
+
+            let lessons = api::lessons_range_request(
+                &start.format("%Y%m%d").to_string(),
+                &end.format("%Y%m%d").to_string(),
+            )
+            .await;
+            let agenda = api::agenda_range_request(
+                &start.format("%Y%m%d").to_string(),
+                &(end + Duration::days(CATCHUP_DAYS))
+                    .format("%Y%m%d")
+                    .to_string(),
+            )
+            .await;
+
+            let result = display::display_catchup((start, end), absences, lessons, agenda);
+            println!("{}", result);
+        }
+        Commands::Timetable { update } => {
+            let timetable = get_timetable(update).await;
+            println!("{}", display::display_timetable(&timetable));
+        }
+        Commands::Now => {
+            let timetable = get_timetable(false).await;
+            println!(
+                "{}",
+                display::display_now(&timetable, Local::now().naive_local())
+            );
+        }
+        Commands::Next => {
+            let timetable = get_timetable(false).await;
+            println!(
+                "{}",
+                display::display_next(&timetable, Local::now().naive_local())
+            );
+        }
+        Commands::Syllabus { from, to, markdown } => {
+            let lesson_settings = AgendaSettings::new(settings, None, args.name);
+            // from the start of the school year to today by default
+            let start = match from {
+                Some(date) => parse_date(&date).format("%Y%m%d").to_string(),
+                None => api::get_school_year_start(),
+            };
+            let end = match to {
+                Some(date) => parse_date(&date).format("%Y%m%d").to_string(),
+                None => api::get_today(),
+            };
+
+            let result = api::lessons_range_request(&start, &end).await;
+            let result = or_exit(display::display_syllabus(result, lesson_settings, markdown));
+            println!("{}", result);
+        }
+        Commands::Exams => {
+            let today = Local::now().date_naive();
+            let days = Duration::days(USER_CONFIG.assessment_settings.days as i64);
+            // the past tests are needed to find the topics of the upcoming ones
+            let lessons =
+                api::lessons_range_request(&api::get_school_year_start(), &api::get_today()).await;
+            let agenda = api::agenda_range_request(
+                &api::get_school_year_start(),
+                &(today + days).format("%Y%m%d").to_string(),
+            )
+            .await;
+
+            let result = or_exit(display::display_exams(
+                lessons,
+                agenda,
+                &args.name,
+                today,
+                &api::school_calendar(false).await,
+            ));
+            println!("{}", result);
+        }
+        Commands::Homework {
+            action,
+            all,
+            overdue,
+        } => {
+            let mut state = HomeworkState::load();
+            let today = Local::now().date_naive();
+            let agenda = api::agenda_range_request(
+                &(today - Duration::days(PAST_DAYS))
+                    .format("%Y%m%d")
+                    .to_string(),
+                &(today + Duration::days(NEXT_DAYS))
+                    .format("%Y%m%d")
+                    .to_string(),
+            )
+            .await;
+
+            match action {
+                Some(HomeworkAction::Done { id }) => {
+                    // only the homework of the fetched agenda can be ticked
+                    if !agenda
+                        .agenda
+                        .iter()
+                        .any(|x| x.evtId == id && x.evtCode == "AGHW")
+                    {
+                        eprintln!("No homework with id {}", id);
+                        std::process::exit(1);
+                    }
+                    state.done.insert(id);
+                    state.store();
+                    println!("Homework {} marked as done", id);
+                    return;
+                }
+                Some(HomeworkAction::Undo { id }) => {
+                    state.done.remove(&id);
+                    state.store();
+                    println!("Homework {} marked as not done", id);
+                    return;
+                }
+                None => (),
+            }
+
+            // the lessons give the subject of the homework without one
+            let (start, end) = inference_range(today);
+            let lessons = api::lessons_range_request(
+                &start.format("%Y%m%d").to_string(),
+                &end.format("%Y%m%d").to_string(),
+            )
+            .await;
+
+            let mut homework = collect(&agenda.agenda, &lessons.lessons, &state);
+            homework.retain(|x| (all || !x.done) && (!overdue || x.is_overdue(today)));
+
+            let result = or_exit(display::display_homework(
+                homework,
+                &args.name,
+                today,
+                &api::school_calendar(false).await,
+            ));
+            println!("{}", result);
+        }
+        Commands::Workload { weeks } => {
+            let today = Local::now().date_naive();
+            // from the monday of the current week to the sunday of the last one
+            let start = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+            let weeks = weeks.unwrap_or(DEFAULT_WEEKS).max(1);
+            let end = start + Duration::weeks(weeks as i64) - Duration::days(1);
+
+            let agenda = api::agenda_range_request(
+                &start.format("%Y%m%d").to_string(),
+                &end.format("%Y%m%d").to_string(),
+            )
+            .await;
+            // the lessons give the subject of the events without one
+            let (lessons_start, lessons_end) = inference_range(today);
+            let lessons = api::lessons_range_request(
+                &lessons_start.format("%Y%m%d").to_string(),
+                &lessons_end.format("%Y%m%d").to_string(),
+            )
+            .await;
+
+            let loads = workload(
+                &agenda.agenda,
+                &lessons.lessons,
+                &HomeworkState::load(),
+                (start, end),
+            );
+            let result = or_exit(display::display_workload(loads, &args.name, (start, end)));
+            println!("{}", result);
+        }
+        Commands::Plan { markdown, ics } => {
+            let today = Local::now().date_naive();
+            let days = Duration::days(USER_CONFIG.assessment_settings.days as i64);
+            // the past tests are needed to find the topics of the upcoming ones
+            let lessons =
+                api::lessons_range_request(&api::get_school_year_start(), &api::get_today()).await;
+            let agenda = api::agenda_range_request(
+                &api::get_school_year_start(),
+                &(today + days).format("%Y%m%d").to_string(),
+            )
+            .await;
+            let grades = api::grades_request().await;
+
+            // the sessions start from tomorrow
+            let assessments = detect(&agenda.agenda, &lessons.lessons);
+            let upcoming: Vec<&Assessment> =
+                assessments.iter().filter(|x| x.date > today).collect();
+            let upcoming = or_exit(filter_by_name(upcoming, &args.name, |x| x.subject.clone()));
+            let plan = planner::plan(
+                &upcoming,
+                &assessments,
+                &lessons.lessons,
+                &subject_averages(&grades.grades),
+                today + Duration::days(1),
+            );
+
+            let result = display::display_plan(plan, markdown, ics);
+            println!("{}", result);
+        }
+        Commands::Notes {
+            action,
+            unread,
+            kind,
+        } => {
+            let mut notes = all_notes(api::notes_request().await);
+
+            if let Some(NoteAction::Read { id }) = action {
+                // the category is needed to read a note
+                let (note_kind, note) = match notes.into_iter().find(|(_, x)| x.evtId == id) {
+                    Some(v) => v,
+                    None => {
+                        eprintln!("No note with id {}", id);
+                        std::process::exit(1);
+                    }
+                };
+                let event = api::read_note_request(note_kind.code(), id).await;
+                println!("{}", display::display_note(note_kind, &note, event));
+                return;
+            }
+
+            notes.retain(|(note_kind, note)| {
+                kind.is_none_or(|x| x == *note_kind) && (!unread || !note.readStatus)
+            });
+            let result = display::display_notes(notes, settings);
+            println!("{}", result);
+        }
+        Commands::Board {
+            action,
+            unread,
+            category,
+        } => {
+            let mut notices = api::noticeboard_request().await.items;
+
+            let id = match &action {
+                Some(BoardAction::Read { id }) | Some(BoardAction::Download { id, .. }) => *id,
+                None => {
+                    notices.retain(|x| {
+                        (!unread || !x.readStatus)
+                            && category.as_ref().is_none_or(|category| {
+                                normalize(&x.cntCategory).contains(&normalize(category))
+                            })
+                    });
+                    println!("{}", display::display_noticeboard(notices, settings));
+                    return;
+                }
+            };
+
+            let notice = match notices.into_iter().find(|x| x.pubId == id) {
+                Some(v) => v,
+                None => {
+                    eprintln!("No communication with id {}", id);
+                    std::process::exit(1);
+                }
+            };
+            match action {
+                Some(BoardAction::Download { dir, .. }) => {
+                    if notice.attachments.is_empty() {
+                        println!("No attachments");
+                        return;
+                    }
+                    let dir = target_dir(dir);
+                    for attachment in &notice.attachments {
+                        let bytes = api::notice_attachment_request(&notice, attachment).await;
+                        let path = dir.join(safe_file_name(&attachment.fileName));
+                        save(&path, &bytes);
+                        println!("Saved {}", path.display());
+                    }
+                }
+                _ => {
+                    let content = api::read_notice_request(&notice).await;
+                    println!("{}", display::display_notice(&notice, content));
+                }
+            }
+        }
+        Commands::Materials { action } => {
+            let didactics = api::didactics_request().await;
+            // the lessons give the subject taught by every teacher
+            let (start, end) = inference_range(Local::now().date_naive());
+            let lessons = api::lessons_range_request(
+                &start.format("%Y%m%d").to_string(),
+                &end.format("%Y%m%d").to_string(),
+            )
+            .await;
+            let materials = materials(didactics, &subjects_by_teacher(&lessons.lessons));
+            let materials = or_exit(filter_by_name(materials, &args.name, |x| x.subject.clone()));
+
+            match action {
+                None => println!("{}", display::display_materials(materials, settings)),
+                Some(MaterialAction::Download { id, dir }) => {
+                    let material = match materials.iter().find(|x| x.id == id) {
+                        Some(v) => v,
+                        None => {
+                            eprintln!("No material with id {}", id);
+                            std::process::exit(1);
+                        }
+                    };
+                    // only the file, without the teacher and the folder
+                    let path = target_dir(dir).join(&material.file_name);
+                    download(material, &path).await;
+                    println!("Saved {}", path.display());
+                }
+                Some(MaterialAction::Mirror { dir }) => {
+                    let dir = target_dir(dir);
+                    let mut skipped = 0;
+                    for material in &materials {
+                        let path = material.local_path(&dir);
+                        if path.exists() {
+                            skipped += 1;
+                            continue;
+                        }
+                        download(material, &path).await;
+                        println!("Saved {}", path.display());
+                    }
+                    println!("{} files already present", skipped);
+                }
+            }
+        }
+        Commands::Documents { action } => {
+            let documents = api::documents_request().await;
+            let mut available: Vec<bool> = Vec::new();
+            for document in &documents.documents {
+                available.push(api::document_check_request(&document.hash).await);
+            }
+            let year = school_year();
+
+            let (id, dir) = match action {
+                Some(DocumentAction::Download { id, dir }) => (id, target_dir(dir)),
+                None => {
+                    let result = display::display_documents(documents, available, year);
+                    println!("{}", result);
+                    return;
+                }
+            };
+
+            let descs: Vec<&str> = documents.documents.iter().map(|x| &x.desc[..]).collect();
+            let names = file_names(&descs, year);
+            if id.is_some_and(|id| id == 0 || id > documents.documents.len()) {
+                eprintln!("No document with id {}", id.unwrap_or_default());
+                std::process::exit(1);
+            }
+            for (index, document) in documents.documents.iter().enumerate() {
+                if id.is_some_and(|id| id != index + 1) {
+                    continue;
+                }
+                if !available[index] {
+                    println!("{} is not available", document.desc.trim());
+                    continue;
+                }
+                let bytes = api::document_request(&document.hash).await;
+                let path = dir.join(&names[index]);
+                save(&path, &bytes);
+                println!("Saved {}", path.display());
+            }
+        }
+        Commands::Subjects => {
+            let subjects = api::subjects_request().await;
+            let lessons =
+                api::lessons_range_request(&api::get_school_year_start(), &api::get_today()).await;
+            let grades = api::grades_request().await;
+
+            let result = or_exit(display::display_subjects(
+                subjects, lessons, grades, &args.name,
+            ));
+            println!("{}", result);
+        }
+        Commands::Calendar { update } => {
+            let calendar = api::school_calendar(update).await;
+            let result = display::display_calendar(&calendar, Local::now().date_naive());
+            println!("{}", result);
+        }
+        Commands::Me => {
+            let card = api::profile_card(true).await;
+            // the card has no class, the one of the last lesson is used
+            let (start, end) = inference_range(Local::now().date_naive());
+            let lessons = api::lessons_range_request(
+                &start.format("%Y%m%d").to_string(),
+                &end.format("%Y%m%d").to_string(),
+            )
+            .await;
+            let class = lessons
+                .lessons
+                .iter()
+                .max_by(|a, b| a.evtDate.cmp(&b.evtDate))
+                .map(|x| x.classDesc.trim().to_string());
+
+            println!("{}", display::display_card(card, class));
+        }
+        Commands::Search { query, from, to } => {
+            // from the start of the school year to today by default
+            let start = match from {
+                Some(date) => parse_date(&date).format("%Y%m%d").to_string(),
+                None => api::get_school_year_start(),
+            };
+            let end = match to {
+                Some(date) => parse_date(&date).format("%Y%m%d").to_string(),
+                None => api::get_today(),
+            };
+
+            let lessons = api::lessons_range_request(&start, &end).await;
+            let agenda = api::agenda_range_request(&start, &end).await;
+            let mut grades = api::grades_request().await;
+            // grades are fetched all together, dates are compared as YYYYMMDD
+            grades.grades.retain(|x| {
+                let date = x.evtDate.replace('-', "");
+                date >= start && date <= end
+            });
+
+            let result = or_exit(display::display_search(
+                &query, lessons, agenda, grades, &args.name,
+            ));
+            println!("{}", result);
+        }
+        Commands::Agenda { kind, teacher } => {
+            let agenda_settings = AgendaSettings::new(settings, args.date, args.name);
+            let agenda_filter = AgendaFilter::new(kind, teacher);
+            let result = api::agenda_request(agenda_settings.date.clone()).await;
+            let result = or_exit(display::display_agenda(
+                result,
+                agenda_settings,
+                agenda_filter,
+            ));
             println!("{}", result);
         }
-        Commands::Lesson => {
-            let lesson_settings = AgendaSettings::new(settings, args.date);
-            let result = api::lessons_request(lesson_settings.date).await;
-            let result = display::display_lessons(result);
+        Commands::Lesson { grid } => {
+            let lesson_settings = AgendaSettings::new(settings, args.date, args.name);
+            let result = api::lessons_request(lesson_settings.date.clone()).await;
+            let result = or_exit(if grid {
+                display::display_lesson_grid(result, lesson_settings)
+            } else {
+                display::display_lessons(result, lesson_settings)
+            });
             println!("{}", result);
         }
     }
@@ -1,8 +1,26 @@
+mod absences;
+mod agenda;
 mod api;
+mod assessments;
+mod calendar;
+mod credit;
 mod display;
+mod documents;
+mod downloads;
+mod homework;
 mod input;
+mod materials;
+mod notes;
+mod planner;
 mod response_types;
+mod risk;
+mod search;
 mod settings;
+mod stats;
+mod subjects;
+mod syllabus;
+mod timetable;
+mod workload;
 use lazy_static::lazy_static;
 use settings::*;
 use std::sync::Mutex;
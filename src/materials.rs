@@ -0,0 +1,200 @@
+// Module: materials
+use crate::api;
+use crate::downloads::{safe_file_name, save};
+use crate::response_types::{DidacticItem, Didactics};
+use crate::subjects::{normalize, Subject};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+// a content of a folder of the didactic materials
+pub struct Material {
+    pub id: u32,
+    pub teacher: String,
+    pub subject: Option<Subject>,
+    pub folder: String,
+    pub name: String,
+    pub kind: String,
+    pub shared: String,
+    // name of the file, unique within its folder
+    pub file_name: String,
+}
+
+impl Material {
+    // path of the material in a mirror of the didactic materials: teacher/folder/file
+    pub fn local_path(&self, dir: &Path) -> PathBuf {
+        dir.join(safe_file_name(&self.teacher))
+            .join(safe_file_name(&self.folder))
+            .join(&self.file_name)
+    }
+}
+
+// links are saved as .url files and texts as .txt files, the files keep their extension
+fn file_name(name: &str, kind: &str) -> String {
+    let name = safe_file_name(name);
+    let extension = match kind {
+        "link" => "url",
+        "text" => "txt",
+        _ => return name,
+    };
+    match Path::new(&name).extension() {
+        Some(v) if v.eq_ignore_ascii_case(extension) => name,
+        _ => format!("{}.{}", name, extension),
+    }
+}
+
+// the same name in a folder gets a number before the extension, e.g. esercizi-2.pdf
+fn unique_name(name: String, taken: &mut HashSet<String>) -> String {
+    let path = Path::new(&name);
+    let (stem, extension) = match (path.file_stem(), path.extension()) {
+        (Some(stem), Some(extension)) => (
+            stem.to_string_lossy().to_string(),
+            format!(".{}", extension.to_string_lossy()),
+        ),
+        _ => (name.clone(), String::new()),
+    };
+
+    let mut unique = name.clone();
+    let mut count = 1;
+    // compared ignoring the case, some file systems do not tell them apart
+    while !taken.insert(unique.to_lowercase()) {
+        count += 1;
+        unique = format!("{}-{}{}", stem, count, extension);
+    }
+    unique
+}
+
+// every content of the didactic materials, the subject is the one taught by its teacher
+pub fn materials(didactics: Didactics, teachers: &HashMap<String, Subject>) -> Vec<Material> {
+    let mut result: Vec<Material> = Vec::new();
+    // names already used in each directory of the mirror
+    let mut taken: HashMap<(String, String), HashSet<String>> = HashMap::new();
+    for teacher in didactics.didacticts {
+        let subject = teachers.get(&normalize(&teacher.teacherName)).cloned();
+        for folder in teacher.folders {
+            let taken = taken
+                .entry((
+                    safe_file_name(&teacher.teacherName),
+                    safe_file_name(&folder.folderName),
+                ))
+                .or_default();
+            for content in folder.contents {
+                let file_name =
+                    unique_name(file_name(&content.contentName, &content.objectType), taken);
+                result.push(Material {
+                    id: content.contentId,
+                    teacher: teacher.teacherName.trim().to_string(),
+                    subject: subject.clone(),
+                    folder: folder.folderName.trim().to_string(),
+                    name: content.contentName.trim().to_string(),
+                    kind: content.objectType,
+                    shared: content
+                        .shareDT
+                        .unwrap_or_default()
+                        .chars()
+                        .take(10)
+                        .collect(),
+                    file_name,
+                });
+            }
+        }
+    }
+
+    result
+}
+
+// download a material to a path, a link becomes an internet shortcut
+pub async fn download(material: &Material, path: &Path) {
+    let bytes = api::didactic_item_request(material.id).await;
+    let bytes = match &material.kind[..] {
+        "link" | "text" => {
+            let item: DidacticItem = match serde_json::from_slice(&bytes) {
+                Ok(v) => v,
+                Err(e) => panic!("[ERROR]: Parsing didactic item response: {}", e),
+            };
+            match (item.item.link, item.item.text) {
+                (Some(link), _) => format!("[InternetShortcut]\nURL={}\n", link).into_bytes(),
+                (None, Some(text)) => text.into_bytes(),
+                (None, None) => Vec::new(),
+            }
+        }
+        _ => bytes,
+    };
+
+    save(path, &bytes);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_names_follow_the_kind() {
+        assert_eq!(file_name("Esercizi.pdf", "file"), "Esercizi.pdf");
+        assert_eq!(file_name("Video lezione", "link"), "Video lezione.url");
+        assert_eq!(file_name("appunti.TXT", "text"), "appunti.TXT");
+        assert_eq!(file_name("../Compiti", "text"), "Compiti.txt");
+    }
+
+    #[test]
+    fn unique_names_ignore_the_case() {
+        let mut taken = HashSet::new();
+        let names: Vec<String> = [
+            "esercizi.pdf",
+            "Esercizi.PDF",
+            "esercizi.pdf",
+            "note",
+            "note",
+        ]
+        .iter()
+        .map(|x| unique_name(x.to_string(), &mut taken))
+        .collect();
+        assert_eq!(
+            names,
+            vec![
+                "esercizi.pdf",
+                "Esercizi-2.PDF",
+                "esercizi-3.pdf",
+                "note",
+                "note-2"
+            ]
+        );
+    }
+
+    #[test]
+    fn materials_are_mirrored_by_teacher_and_folder() {
+        let didactics: Didactics = serde_json::from_value(serde_json::json!({
+            "didacticts": [{
+                "teacherId": "R1",
+                "teacherName": "ROSSI MARIO ",
+                "teacherFirstName": "MARIO",
+                "teacherLastName": "ROSSI",
+                "folders": [{
+                    "folderId": 1,
+                    "folderName": "Unità 1/2",
+                    "lastShareDT": "2025-10-06T09:00:00+02:00",
+                    "contents": [
+                        { "contentId": 10, "contentName": "Esercizi.pdf", "objectId": 1,
+                          "objectType": "file", "shareDT": "2025-10-06T09:00:00+02:00" },
+                        { "contentId": 11, "contentName": "esercizi.pdf", "objectId": 2,
+                          "objectType": "file", "shareDT": null },
+                    ],
+                }],
+            }],
+        }))
+        .unwrap();
+        let teachers = HashMap::from([(
+            normalize("ROSSI MARIO"),
+            Subject::new(Some(1), None, "MATEMATICA"),
+        )]);
+
+        let materials = materials(didactics, &teachers);
+        assert_eq!(materials.len(), 2);
+        assert_eq!(materials[0].subject.as_ref().unwrap().desc, "MATEMATICA");
+        assert_eq!(materials[0].shared, "2025-10-06");
+        // a folder name never becomes a directory of its own
+        assert_eq!(
+            materials[1].local_path(Path::new("mirror")),
+            Path::new("mirror/ROSSI MARIO/2/esercizi-2.pdf")
+        );
+    }
+}
@@ -0,0 +1,100 @@
+// Module: notes
+use crate::response_types::{Note, Notes};
+use clap::ValueEnum;
+
+// categories of the notes, used by the --type filter
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NoteKind {
+    Teacher,
+    Class,
+    Warning,
+    Sanction,
+}
+
+impl NoteKind {
+    // code of the category, used by the server
+    pub fn code(&self) -> &'static str {
+        match self {
+            NoteKind::Teacher => "NTTE",
+            NoteKind::Class => "NTCL",
+            NoteKind::Warning => "NTWN",
+            NoteKind::Sanction => "NTST",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            NoteKind::Teacher => "Annotazione",
+            NoteKind::Class => "Nota disciplinare",
+            NoteKind::Warning => "Richiamo",
+            NoteKind::Sanction => "Sanzione",
+        }
+    }
+}
+
+// every note with its category
+pub fn all_notes(notes: Notes) -> Vec<(NoteKind, Note)> {
+    let mut result: Vec<(NoteKind, Note)> = Vec::new();
+    for (kind, notes) in [
+        (NoteKind::Teacher, notes.NTTE),
+        (NoteKind::Class, notes.NTCL),
+        (NoteKind::Warning, notes.NTWN),
+        (NoteKind::Sanction, notes.NTST),
+    ] {
+        result.extend(notes.into_iter().map(|x| (kind, x)));
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::response_types::ResponseResult;
+    use serde_json::json;
+
+    fn note(id: u32, text: &str) -> serde_json::Value {
+        json!({
+            "evtId": id,
+            "evtText": text,
+            "evtDate": "2025-10-06",
+            "authorName": "ROSSI MARIO",
+            "readStatus": false,
+        })
+    }
+
+    #[test]
+    fn notes_without_some_categories() {
+        let response = json!({
+            "NTWN": [note(2, "Ritardo reiterato")],
+            "NTCL": [note(1, "Disturbo della lezione")],
+        });
+        let notes = match serde_json::from_value(response).unwrap() {
+            ResponseResult::Notes(v) => v,
+            _ => panic!("not parsed as notes"),
+        };
+
+        let found: Vec<(NoteKind, u32)> = all_notes(notes)
+            .into_iter()
+            .map(|(kind, note)| (kind, note.evtId))
+            .collect();
+        assert_eq!(found, vec![(NoteKind::Class, 1), (NoteKind::Warning, 2)]);
+    }
+
+    #[test]
+    fn other_responses_are_not_taken_as_notes() {
+        let expired = json!({
+            "statusCode": 401,
+            "error": "Auth token expired",
+            "message": "Auth token expired",
+        });
+        assert!(matches!(
+            serde_json::from_value(expired).unwrap(),
+            ResponseResult::ExpiredToken(_)
+        ));
+        assert!(matches!(
+            serde_json::from_value(json!({ "grades": [] })).unwrap(),
+            ResponseResult::Grades(_)
+        ));
+    }
+}
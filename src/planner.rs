@@ -0,0 +1,358 @@
+// Module: planner
+use crate::assessments::{topics_since_previous, Assessment};
+use crate::response_types::{Grade, Lesson};
+use crate::stats::current_averages;
+use crate::subjects::subject_name;
+use crate::timetable::weekday_key;
+use crate::USER_CONFIG;
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use std::collections::{BTreeMap, HashMap};
+
+// a revision session of the study plan
+pub struct Session {
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+    pub subject: String,
+    pub test_date: NaiveDate,
+    pub topics: Vec<String>,
+}
+
+pub struct Plan {
+    pub sessions: Vec<Session>,
+    // subject, date and text of the planned tests
+    pub tests: Vec<(String, NaiveDate, String)>,
+    // subject, test date and sessions that did not fit in the free time
+    pub unscheduled: Vec<(String, NaiveDate, u32)>,
+}
+
+// average of the current period of every subject, keyed by the displayed name
+pub fn subject_averages(grades: &[Grade]) -> HashMap<String, f64> {
+    current_averages(grades)
+        .into_iter()
+        .filter_map(|(id, average)| {
+            let grade = grades.iter().find(|x| x.subjectId == id)?;
+            let subject = subject_name(
+                Some(grade.subjectId),
+                Some(&grade.subjectCode),
+                &grade.subjectDesc,
+            );
+            Some((subject, average))
+        })
+        .collect()
+}
+
+/*
+Sessions needed by a test, the weaker the subject the more sessions
+an average of 8 or more gets the configured sessions, an average of 6 twice as many
+a subject without grades is in between
+*/
+fn sessions_needed(average: Option<f64>) -> u32 {
+    let weight = match average {
+        Some(average) => ((10.0 - average) / 2.0).clamp(1.0, 3.0),
+        None => 1.5,
+    };
+    (USER_CONFIG.planner_settings.sessions as f64 * weight).round() as u32
+}
+
+// parse a free time slot of config.toml, e.g. "15:00-17:00"
+fn parse_slot(slot: &str) -> (NaiveTime, NaiveTime) {
+    let parsed = slot.split_once('-').and_then(|(start, end)| {
+        Some((
+            NaiveTime::parse_from_str(start.trim(), "%H:%M").ok()?,
+            NaiveTime::parse_from_str(end.trim(), "%H:%M").ok()?,
+        ))
+    });
+    match parsed {
+        Some(v) => v,
+        None => panic!(
+            "error at parsing planner free time: {} is not HH:MM-HH:MM",
+            slot
+        ),
+    }
+}
+
+// the free time of a day split in sessions
+fn day_sessions(date: NaiveDate) -> Vec<(NaiveDateTime, NaiveDateTime)> {
+    let planner_settings = &USER_CONFIG.planner_settings;
+    let length = Duration::minutes(planner_settings.session_length.max(1) as i64);
+    let slots = match planner_settings.free_time.get(weekday_key(date.weekday())) {
+        Some(v) => v,
+        None => return Vec::new(),
+    };
+
+    let mut sessions: Vec<(NaiveDateTime, NaiveDateTime)> = Vec::new();
+    for slot in slots {
+        let (start, end) = parse_slot(slot);
+        let mut session_start = date.and_time(start);
+        while session_start + length <= date.and_time(end) {
+            sessions.push((session_start, session_start + length));
+            session_start += length;
+        }
+    }
+    sessions.sort();
+    sessions
+}
+
+/*
+Schedule the revision sessions of the upcoming tests
+the closest tests are scheduled first, and their sessions are spread backwards from the day
+before the test: one session a day, going back again when every day has got one
+*/
+pub fn plan(
+    upcoming: &[&Assessment],
+    assessments: &[Assessment],
+    lessons: &[Lesson],
+    averages: &HashMap<String, f64>,
+    today: NaiveDate,
+) -> Plan {
+    let days_before = USER_CONFIG.planner_settings.days.max(1) as i64;
+    let mut free: BTreeMap<NaiveDate, Vec<(NaiveDateTime, NaiveDateTime)>> = BTreeMap::new();
+    let mut sessions: Vec<Session> = Vec::new();
+    let mut unscheduled: Vec<(String, NaiveDate, u32)> = Vec::new();
+    let mut tests: Vec<(String, NaiveDate, String)> = Vec::new();
+
+    let mut upcoming: Vec<&&Assessment> = upcoming.iter().collect();
+    upcoming.sort_by_key(|x| x.date);
+    for assessment in upcoming {
+        let subject = assessment.subject_name();
+        tests.push((subject.clone(), assessment.date, assessment.text.clone()));
+        let topics: Vec<String> = topics_since_previous(assessment, assessments, lessons)
+            .into_iter()
+            .map(|x| x.topic)
+            .collect();
+
+        let first_day = std::cmp::max(today, assessment.date - Duration::days(days_before));
+        let days: Vec<NaiveDate> = (1..=days_before)
+            .map(|offset| assessment.date - Duration::days(offset))
+            .filter(|x| *x >= first_day)
+            .collect();
+
+        let mut needed = sessions_needed(averages.get(&subject).cloned());
+        while needed > 0 {
+            let mut placed = false;
+            for day in &days {
+                if needed == 0 {
+                    break;
+                }
+                let day_free = free.entry(*day).or_insert_with(|| day_sessions(*day));
+                if day_free.is_empty() {
+                    continue;
+                }
+                let (start, end) = day_free.remove(0);
+                sessions.push(Session {
+                    start,
+                    end,
+                    subject: subject.clone(),
+                    test_date: assessment.date,
+                    topics: topics.clone(),
+                });
+                needed -= 1;
+                placed = true;
+            }
+            if !placed {
+                unscheduled.push((subject.clone(), assessment.date, needed));
+                break;
+            }
+        }
+    }
+
+    sessions.sort_by_key(|x| x.start);
+    Plan {
+        sessions,
+        tests,
+        unscheduled,
+    }
+}
+
+// escape a text of an ICS file, a carriage return would end the line
+fn ics_escape(text: &str) -> String {
+    text.replace('\r', "")
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/*
+Fold a line of an ICS file longer than 75 octets, as in RFC 5545 3.1
+the following lines start with a space, a character is never split
+*/
+fn ics_fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            length = 1;
+        }
+        folded.push(c);
+        length += c.len_utf8();
+    }
+    folded
+}
+
+// the study plan as an ICS calendar, times are local and the tests are all-day events
+pub fn to_ics(plan: &Plan) -> String {
+    let stamp = Utc::now().format("%Y%m%dT%H%M%SZ");
+    let mut lines: Vec<String> = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//terminalviva//plan//IT".to_string(),
+    ];
+    for session in &plan.sessions {
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!(
+            "UID:{}-{}@terminalviva",
+            session.start.format("%Y%m%dT%H%M%S"),
+            ics_escape(&session.subject).replace(' ', "-")
+        ));
+        lines.push(format!("DTSTAMP:{}", stamp));
+        lines.push(format!("DTSTART:{}", session.start.format("%Y%m%dT%H%M%S")));
+        lines.push(format!("DTEND:{}", session.end.format("%Y%m%dT%H%M%S")));
+        lines.push(format!("SUMMARY:Ripasso {}", ics_escape(&session.subject)));
+        let mut description = format!("Verifica del {}", session.test_date.format("%Y-%m-%d"));
+        for topic in &session.topics {
+            description.push_str(&format!("\n{}", topic));
+        }
+        lines.push(format!("DESCRIPTION:{}", ics_escape(&description)));
+        lines.push("END:VEVENT".to_string());
+    }
+    for (subject, date, text) in &plan.tests {
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!(
+            "UID:{}-test-{}@terminalviva",
+            date.format("%Y%m%d"),
+            ics_escape(subject).replace(' ', "-")
+        ));
+        lines.push(format!("DTSTAMP:{}", stamp));
+        lines.push(format!("DTSTART;VALUE=DATE:{}", date.format("%Y%m%d")));
+        lines.push(format!(
+            "DTEND;VALUE=DATE:{}",
+            (*date + Duration::days(1)).format("%Y%m%d")
+        ));
+        lines.push(format!("SUMMARY:Verifica {}", ics_escape(subject)));
+        lines.push(format!("DESCRIPTION:{}", ics_escape(text)));
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    // lines of an ICS file end with CRLF
+    lines
+        .iter()
+        .map(|x| ics_fold(x))
+        .collect::<Vec<String>>()
+        .join("\r\n")
+        + "\r\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::subjects::Subject;
+
+    /*
+    config.example.toml: sessions of 45 minutes from 15:00 to 17:00, monday to friday
+    2 sessions for a good average, placed in the 7 days before the test
+    2025-10-06 is a monday
+    */
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 10, day).unwrap()
+    }
+
+    fn at(day: u32, time: &str) -> NaiveDateTime {
+        date(day).and_time(NaiveTime::parse_from_str(time, "%H:%M").unwrap())
+    }
+
+    fn assessment(id: u32, desc: &str, day: u32) -> Assessment {
+        Assessment {
+            id: Some(id),
+            date: date(day),
+            subject: Some(Subject::new(Some(id), None, desc)),
+            text: "verifica".to_string(),
+            teacher: String::new(),
+        }
+    }
+
+    #[test]
+    fn sessions_depend_on_the_average() {
+        assert_eq!(sessions_needed(Some(8.5)), 2);
+        assert_eq!(sessions_needed(Some(6.0)), 4);
+        assert_eq!(sessions_needed(Some(3.0)), 6);
+        assert_eq!(sessions_needed(None), 3);
+    }
+
+    #[test]
+    fn sessions_are_spread_backwards() {
+        let assessments = vec![assessment(1, "FISICA", 10)];
+        let upcoming: Vec<&Assessment> = assessments.iter().collect();
+        let averages = HashMap::from([("FISICA".to_string(), 8.0)]);
+        let plan = plan(&upcoming, &assessments, &[], &averages, date(6));
+
+        let starts: Vec<NaiveDateTime> = plan.sessions.iter().map(|x| x.start).collect();
+        assert_eq!(starts, vec![at(8, "15:00"), at(9, "15:00")]);
+        assert_eq!(plan.sessions[0].end, at(8, "15:45"));
+        assert_eq!(
+            plan.tests,
+            vec![("FISICA".to_string(), date(10), "verifica".to_string())]
+        );
+        assert!(plan.unscheduled.is_empty());
+    }
+
+    #[test]
+    fn closest_tests_come_first() {
+        let assessments = vec![assessment(1, "FISICA", 9), assessment(2, "CHIMICA", 8)];
+        let upcoming: Vec<&Assessment> = assessments.iter().collect();
+        let averages = HashMap::from([("FISICA".to_string(), 8.0), ("CHIMICA".to_string(), 8.0)]);
+        let plan = plan(&upcoming, &assessments, &[], &averages, date(6));
+
+        let sessions: Vec<(NaiveDateTime, &str)> = plan
+            .sessions
+            .iter()
+            .map(|x| (x.start, x.subject.as_str()))
+            .collect();
+        assert_eq!(
+            sessions,
+            vec![
+                (at(6, "15:00"), "CHIMICA"),
+                (at(7, "15:00"), "CHIMICA"),
+                (at(7, "15:45"), "FISICA"),
+                (at(8, "15:00"), "FISICA"),
+            ]
+        );
+    }
+
+    #[test]
+    fn sessions_that_do_not_fit_are_reported() {
+        let assessments = vec![assessment(1, "FISICA", 8)];
+        let upcoming: Vec<&Assessment> = assessments.iter().collect();
+        let averages = HashMap::from([("FISICA".to_string(), 5.0)]);
+        let plan = plan(&upcoming, &assessments, &[], &averages, date(6));
+
+        // two days with two sessions each, five are needed
+        assert_eq!(plan.sessions.len(), 4);
+        assert_eq!(plan.unscheduled, vec![("FISICA".to_string(), date(8), 1)]);
+    }
+
+    #[test]
+    fn ics_text_is_escaped() {
+        assert_eq!(ics_escape("a\\b; c, d\r\ne"), "a\\\\b\\; c\\, d\\ne");
+    }
+
+    #[test]
+    fn long_ics_lines_are_folded() {
+        assert_eq!(ics_fold("SUMMARY:Ripasso"), "SUMMARY:Ripasso");
+
+        let line = format!("DESCRIPTION:{}", "è".repeat(70));
+        let folded = ics_fold(&line);
+        let lines: Vec<&str> = folded.split("\r\n").collect();
+        assert_eq!(lines.len(), 3);
+        // no line is longer than 75 octets and no character is split
+        assert!(lines.iter().all(|x| x.len() <= 75));
+        assert!(lines[1..].iter().all(|x| x.starts_with(' ')));
+        let unfolded: String = lines
+            .iter()
+            .map(|x| x.strip_prefix(' ').unwrap_or(x))
+            .collect();
+        assert_eq!(unfolded, line);
+    }
+}
@@ -7,11 +7,24 @@ use serde_json::Value;
 #[derive(Deserialize)]
 #[serde(untagged)]
 pub enum ResponseResult {
+    #[allow(dead_code)]
     ExpiredToken(ExpiredToken),
     Grades(Grades),
     Absences(Absences),
     Agendas(Agendas),
     Lessons(Lessons),
+    NoteRead(NoteRead),
+    Noticeboard(Noticeboard),
+    NoticeRead(NoticeRead),
+    Didactics(Didactics),
+    Documents(Documents),
+    DocumentCheck(DocumentCheck),
+    Subjects(Subjects),
+    Calendar(Calendar),
+    // boxed, the card is much larger than the other payloads
+    Card(Box<CardResponse>),
+    // last, every field of the notes is optional so any object would match
+    Notes(Notes),
 }
 #[derive(Serialize, Deserialize, Debug)]
 pub struct ExpiredToken {
@@ -126,11 +139,16 @@ pub enum LoginResponse {
 
 #[derive(Deserialize, Debug)]
 pub struct LoginPayload {
+    #[allow(dead_code)]
     pub expire: String,
+    #[allow(dead_code)]
     pub firstName: String,
     pub ident: String,
+    #[allow(dead_code)]
     pub lastName: String,
+    #[allow(dead_code)]
     pub release: String,
+    #[allow(dead_code)]
     pub showPwdChangeReminder: bool,
     pub token: String,
     pub tokenAP: String,
@@ -140,8 +158,11 @@ pub struct LoginPayload {
 
 #[derive(Deserialize, Debug)]
 pub struct LoginError {
+    #[allow(dead_code)]
     pub statusCode: u16,
+    #[allow(dead_code)]
     pub error: String,
+    #[allow(dead_code)]
     pub info: String,
     pub message: String,
 }
@@ -174,3 +195,360 @@ pub struct Lesson {
     pub lessonType: Option<String>,
     pub lessonArg: String,
 }
+
+/*
+Notes of the teachers, grouped by category
+NTTE: note of a teacher
+NTCL: disciplinary note on the class register
+NTWN: warning
+NTST: disciplinary sanction
+*/
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Notes {
+    // a category without notes may be left out
+    #[serde(default)]
+    pub NTTE: Vec<Note>,
+    #[serde(default)]
+    pub NTCL: Vec<Note>,
+    #[serde(default)]
+    pub NTWN: Vec<Note>,
+    #[serde(default)]
+    pub NTST: Vec<Note>,
+}
+
+impl Notes {
+    pub fn new() -> Self {
+        Notes {
+            NTTE: Vec::new(),
+            NTCL: Vec::new(),
+            NTWN: Vec::new(),
+            NTST: Vec::new(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Note {
+    pub evtId: u32,
+    // the text of an unread note can be hidden until the note is read
+    pub evtText: String,
+    pub evtDate: String,
+    pub authorName: String,
+    pub readStatus: bool,
+    // only sent with the warnings
+    pub warningType: Option<String>,
+}
+
+// response of the request marking a note as read
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NoteRead {
+    pub event: NoteEvent,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NoteEvent {
+    pub evtId: u32,
+    pub evtCode: String,
+    pub evtText: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Noticeboard {
+    pub items: Vec<Notice>,
+}
+
+impl Noticeboard {
+    pub fn new() -> Self {
+        Noticeboard { items: Vec::new() }
+    }
+}
+
+// a communication of the noticeboard, e.g. a circular of the school
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Notice {
+    pub pubId: u32,
+    pub pubDT: String,
+    pub readStatus: bool,
+    pub evtCode: String,
+    pub cntId: Option<u32>,
+    pub cntValidFrom: Option<String>,
+    pub cntValidTo: Option<String>,
+    pub cntValidInRange: Option<bool>,
+    pub cntStatus: Option<String>,
+    pub cntTitle: String,
+    pub cntCategory: String,
+    pub cntHasChanged: Option<bool>,
+    pub cntHasAttach: bool,
+    pub needJoin: Option<bool>,
+    pub needReply: Option<bool>,
+    pub needFile: Option<bool>,
+    pub attachments: Vec<NoticeAttachment>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NoticeAttachment {
+    pub fileName: String,
+    pub attachNum: u32,
+}
+
+// response of the request marking a communication as read
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NoticeRead {
+    pub item: NoticeContent,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NoticeContent {
+    pub title: Option<String>,
+    pub text: Option<String>,
+}
+
+// the misspelling of the field is not an error, it is sent by the server this way
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Didactics {
+    pub didacticts: Vec<DidacticTeacher>,
+}
+
+impl Didactics {
+    pub fn new() -> Self {
+        Didactics {
+            didacticts: Vec::new(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DidacticTeacher {
+    pub teacherId: Option<Value>,
+    pub teacherName: String,
+    pub teacherFirstName: Option<String>,
+    pub teacherLastName: Option<String>,
+    pub folders: Vec<DidacticFolder>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DidacticFolder {
+    pub folderId: u32,
+    pub folderName: String,
+    pub lastShareDT: Option<String>,
+    pub contents: Vec<DidacticContent>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DidacticContent {
+    pub contentId: u32,
+    pub contentName: String,
+    pub objectId: Option<Value>,
+    // file, link or text
+    pub objectType: String,
+    pub shareDT: Option<String>,
+}
+
+// a link or a text of the didactic materials, the files are sent as they are
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DidacticItem {
+    pub item: DidacticItemContent,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DidacticItemContent {
+    pub link: Option<String>,
+    pub text: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Documents {
+    pub documents: Vec<Document>,
+    pub schoolReports: Vec<SchoolReport>,
+}
+
+impl Documents {
+    pub fn new() -> Self {
+        Documents {
+            documents: Vec::new(),
+            schoolReports: Vec::new(),
+        }
+    }
+}
+
+// a document of the school, e.g. a report card, identified by its hash
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Document {
+    pub hash: String,
+    pub desc: String,
+}
+
+// report cards that can only be seen on the website
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SchoolReport {
+    pub desc: String,
+    pub confirmLink: Option<String>,
+    pub viewLink: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DocumentCheck {
+    pub document: DocumentAvailability,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DocumentAvailability {
+    pub available: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Subjects {
+    pub subjects: Vec<SubjectInfo>,
+}
+
+impl Subjects {
+    pub fn new() -> Self {
+        Subjects {
+            subjects: Vec::new(),
+        }
+    }
+}
+
+// a subject of the class, its id is the subjectId of grades, lessons and agenda
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SubjectInfo {
+    pub id: u32,
+    pub description: String,
+    pub order: Option<u32>,
+    pub teachers: Vec<SubjectTeacher>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SubjectTeacher {
+    pub teacherId: Option<Value>,
+    pub teacherName: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Calendar {
+    pub calendar: Vec<CalendarDay>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CalendarDay {
+    pub dayDate: String,
+    pub dayOfWeek: u32,
+    // TODO: find out each code's meaning
+    pub dayStatus: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CardResponse {
+    pub card: Card,
+}
+
+// profile of the current user
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Card {
+    pub ident: String,
+    // S: student, G: parent, T: teacher
+    pub usrType: String,
+    pub usrId: u32,
+    pub miurSchoolCode: Option<String>,
+    pub miurDivisionCode: Option<String>,
+    pub firstName: String,
+    pub lastName: String,
+    pub birthDate: Option<String>,
+    pub fiscalCode: Option<String>,
+    pub schCode: String,
+    pub schName: String,
+    pub schDedication: Option<String>,
+    pub schCity: String,
+    pub schProv: Option<String>,
+}
+
+// records of the server used by the tests of the other modules, the fields can be changed afterwards
+#[cfg(test)]
+pub mod fixtures {
+    use super::*;
+    use serde_json::json;
+
+    // a grade of the first period with weight 1
+    pub fn grade(subject_id: u32, subject: &str, date: &str, value: f64) -> Grade {
+        serde_json::from_value(json!({
+            "subjectId": subject_id,
+            "subjectCode": "",
+            "subjectDesc": subject,
+            "evtId": 1,
+            "evtDate": date,
+            "decimalValue": value,
+            "displayValue": value.to_string(),
+            "displaPos": 1,
+            "notesForFamily": "",
+            "color": "green",
+            "canceled": false,
+            "underlined": false,
+            "periodPos": 1,
+            "periodDesc": "Primo periodo",
+            "componentPos": 1,
+            "componentDesc": "Scritto",
+            "weightFactor": 1.0,
+            "skillId": 0,
+            "gradeMasterId": 0,
+            "skillDesc": null,
+            "skillCode": null,
+            "skillMasterId": 0,
+            "skillValueDesc": "",
+            "skillValueShortDesc": null,
+            "oldskillId": 0,
+            "oldskillDesc": "",
+        }))
+        .unwrap()
+    }
+
+    // a lesson of one hour
+    pub fn lesson(date: &str, position: u32, subject: Option<(u32, &str)>, topic: &str) -> Lesson {
+        Lesson {
+            evtId: 1,
+            evtDate: date.to_string(),
+            evtCode: "LSF0".to_string(),
+            evtHPos: position,
+            evtDuration: 1,
+            classDesc: "4A".to_string(),
+            authorName: "ROSSI MARIO".to_string(),
+            subjectId: subject.map(|(id, _)| id),
+            subjectCode: None,
+            subjectDesc: subject.map(|(_, desc)| desc.to_string()),
+            lessonType: None,
+            lessonArg: topic.to_string(),
+        }
+    }
+
+    // an absence event, hours are the positions listed by the server
+    pub fn absence(code: &str, date: &str, position: Option<u32>, hours: Value) -> Absence {
+        serde_json::from_value(json!({
+            "evtId": 1,
+            "evtCode": code,
+            "evtDate": date,
+            "evtHPos": position,
+            "evtValue": null,
+            "isJustified": false,
+            "justifReasonCode": null,
+            "justifReasonDesc": null,
+            "hoursAbsence": hours,
+        }))
+        .unwrap()
+    }
+
+    // an event of the agenda, begin is YYYY-MM-DDTHH:MM:SS+offset
+    pub fn agenda(id: u32, code: &str, begin: &str, notes: &str) -> Agenda {
+        Agenda {
+            evtId: id,
+            evtCode: code.to_string(),
+            evtDatetimeBegin: begin.to_string(),
+            evtDatetimeEnd: begin.to_string(),
+            isFullDay: false,
+            notes: notes.to_string(),
+            authorName: "ROSSI MARIO".to_string(),
+            classDesc: "4A".to_string(),
+            subjectId: None,
+            subjectDesc: None,
+            homeworkId: None,
+        }
+    }
+}
@@ -0,0 +1,279 @@
+// Module: risk
+use crate::response_types::Grade;
+use crate::stats::{rule_average, PASSING_GRADE};
+use crate::subjects::{find_by_subject, normalize, subject_name};
+use crate::USER_CONFIG;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Low,
+    Medium,
+    High,
+}
+
+impl Severity {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Severity::Low => "low",
+            Severity::Medium => "medium",
+            Severity::High => "high",
+        }
+    }
+}
+
+pub struct SubjectRisk {
+    pub subject: String,
+    // average of the current period, None if there are no grades
+    pub average: Option<f64>,
+    pub goal: f64,
+    pub reasons: Vec<(Severity, String)>,
+}
+
+impl SubjectRisk {
+    pub fn severity(&self) -> Severity {
+        self.reasons
+            .iter()
+            .map(|(severity, _)| *severity)
+            .max()
+            .unwrap_or(Severity::Low)
+    }
+
+    // how far the average is below the goal, used to sort subjects with the same severity
+    fn gap(&self) -> f64 {
+        match self.average {
+            Some(average) => self.goal - average,
+            None => 0.0,
+        }
+    }
+}
+
+pub struct RiskReport {
+    pub period: String,
+    pub risks: Vec<SubjectRisk>,
+}
+
+// the goal of a subject can be keyed by id, code, description or by its alias
+fn subject_goal(grade: &Grade, subject: &str) -> f64 {
+    let risk_settings = &USER_CONFIG.risk_settings;
+    let goal = find_by_subject(
+        &risk_settings.goals,
+        Some(grade.subjectId),
+        Some(&grade.subjectCode),
+        &grade.subjectDesc,
+    )
+    .or_else(|| {
+        risk_settings
+            .goals
+            .iter()
+            .find(|(key, _)| normalize(key) == normalize(subject))
+            .map(|(_, goal)| goal)
+    });
+
+    *goal.unwrap_or(&risk_settings.threshold)
+}
+
+/*
+Flag the subjects at risk
+- high: the average of the current period is below the threshold, or the last grades are all failing
+- medium: the average is below the personal goal of the subject, or the last grades are going down
+- low: there are too few grades in the current period
+*/
+pub fn assess(grades: Vec<Grade>) -> RiskReport {
+    let risk_settings = &USER_CONFIG.risk_settings;
+
+    // the current period is the latest one with a grade
+    let current_period = grades.iter().map(|x| x.periodPos).max().unwrap_or(0);
+    let period = grades
+        .iter()
+        .find(|x| x.periodPos == current_period)
+        .map(|x| x.periodDesc.clone())
+        .unwrap_or_default();
+
+    // canceled grades count neither in the average nor in the streak
+    let mut by_subject: BTreeMap<u32, Vec<Grade>> = BTreeMap::new();
+    for grade in grades.into_iter().filter(|x| !x.canceled) {
+        by_subject.entry(grade.subjectId).or_default().push(grade);
+    }
+
+    let mut risks: Vec<SubjectRisk> = Vec::new();
+    for (subject_id, mut grades) in by_subject {
+        grades.sort_by(|a, b| a.evtDate.cmp(&b.evtDate));
+        let subject = subject_name(
+            Some(subject_id),
+            Some(&grades[0].subjectCode),
+            &grades[0].subjectDesc,
+        );
+        let goal = subject_goal(&grades[0], &subject);
+        let mut reasons: Vec<(Severity, String)> = Vec::new();
+
+        let current: Vec<(f64, f64)> = grades
+            .iter()
+            .filter(|x| x.periodPos == current_period)
+            .map(|x| (x.decimalValue, x.weightFactor))
+            .collect();
+        let average = Some(rule_average(&current)).filter(|x| !x.is_nan());
+
+        if let Some(average) = average {
+            if average < risk_settings.threshold {
+                reasons.push((
+                    Severity::High,
+                    format!(
+                        "average {:.2} below {:.2}",
+                        average, risk_settings.threshold
+                    ),
+                ));
+            } else if average < goal {
+                reasons.push((
+                    Severity::Medium,
+                    format!("average {:.2} below the goal {:.2}", average, goal),
+                ));
+            }
+        }
+
+        // trailing failing grades
+        let streak = grades
+            .iter()
+            .rev()
+            .take_while(|x| x.decimalValue < PASSING_GRADE)
+            .count();
+        if risk_settings.failing_streak > 0 && streak >= risk_settings.failing_streak {
+            reasons.push((
+                Severity::High,
+                format!("{} failing grades in a row", streak),
+            ));
+        }
+
+        // the last grades never go up and the last one is lower than the first one
+        let trend_length = risk_settings.trend_length;
+        if trend_length > 1 && grades.len() >= trend_length {
+            let last: Vec<f64> = grades[grades.len() - trend_length..]
+                .iter()
+                .map(|x| x.decimalValue)
+                .collect();
+            let non_increasing = last.windows(2).all(|x| x[1] <= x[0]);
+            if non_increasing && last[trend_length - 1] < last[0] {
+                let values: Vec<String> = last.iter().map(|x| x.to_string()).collect();
+                reasons.push((
+                    Severity::Medium,
+                    format!("downward trend ({})", values.join(" → ")),
+                ));
+            }
+        }
+
+        if current.len() < risk_settings.min_grades {
+            reasons.push((
+                Severity::Low,
+                format!("only {} grades in the current period", current.len()),
+            ));
+        }
+
+        if !reasons.is_empty() {
+            risks.push(SubjectRisk {
+                subject,
+                average,
+                goal,
+                reasons,
+            });
+        }
+    }
+
+    // the most severe first, then the ones furthest from the goal
+    risks.sort_by(|a, b| {
+        b.severity()
+            .cmp(&a.severity())
+            .then(b.gap().total_cmp(&a.gap()))
+    });
+
+    RiskReport { period, risks }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::response_types::fixtures::grade;
+
+    fn reasons(risk: &SubjectRisk) -> Vec<(Severity, &str)> {
+        risk.reasons
+            .iter()
+            .map(|(severity, reason)| (*severity, reason.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn assess_flags_and_sorts_the_subjects() {
+        let mut canceled = grade(5, "STORIA", "2025-10-20", 2.0);
+        canceled.canceled = true;
+        let report = assess(vec![
+            // below the threshold of config.example.toml
+            grade(1, "FISICA", "2025-10-06", 7.0),
+            grade(1, "FISICA", "2025-10-13", 4.0),
+            grade(1, "FISICA", "2025-10-20", 6.0),
+            // below the goal of 7
+            grade(2, "MATEMATICA", "2025-10-06", 6.5),
+            grade(2, "MATEMATICA", "2025-10-13", 6.5),
+            // the average is exactly the threshold, the last two are failing
+            grade(3, "INGLESE", "2025-10-06", 8.0),
+            grade(3, "INGLESE", "2025-10-13", 5.0),
+            grade(3, "INGLESE", "2025-10-20", 5.0),
+            grade(4, "CHIMICA", "2025-10-06", 8.0),
+            // the canceled grade breaks neither the trend nor the streak
+            grade(5, "STORIA", "2025-10-06", 8.0),
+            grade(5, "STORIA", "2025-10-13", 7.0),
+            grade(5, "STORIA", "2025-10-15", 6.5),
+            canceled,
+            grade(6, "SCIENZE", "2025-10-06", 8.0),
+            grade(6, "SCIENZE", "2025-10-13", 9.0),
+        ]);
+
+        assert_eq!(report.period, "Primo periodo");
+        let subjects: Vec<&str> = report.risks.iter().map(|x| x.subject.as_str()).collect();
+        assert_eq!(
+            subjects,
+            vec!["FISICA", "INGLESE", "MATEMATICA", "STORIA", "CHIMICA"]
+        );
+
+        assert_eq!(
+            reasons(&report.risks[0]),
+            vec![(Severity::High, "average 5.67 below 6.00")]
+        );
+        assert_eq!(
+            reasons(&report.risks[1]),
+            vec![
+                (Severity::High, "2 failing grades in a row"),
+                (Severity::Medium, "downward trend (8 → 5 → 5)"),
+            ]
+        );
+        assert_eq!(
+            reasons(&report.risks[2]),
+            vec![(Severity::Medium, "average 6.50 below the goal 7.00")]
+        );
+        assert_eq!(
+            reasons(&report.risks[3]),
+            vec![(Severity::Medium, "downward trend (8 → 7 → 6.5)")]
+        );
+        assert_eq!(
+            reasons(&report.risks[4]),
+            vec![(Severity::Low, "only 1 grades in the current period")]
+        );
+        assert_eq!(report.risks[4].severity(), Severity::Low);
+    }
+
+    #[test]
+    fn assess_averages_only_the_current_period() {
+        let mut grades = vec![
+            grade(1, "FISICA", "2025-10-06", 4.0),
+            grade(1, "FISICA", "2025-10-13", 4.5),
+            grade(1, "FISICA", "2026-02-09", 7.0),
+            grade(1, "FISICA", "2026-02-16", 8.0),
+        ];
+        for grade in &mut grades[2..] {
+            grade.periodPos = 2;
+            grade.periodDesc = "Secondo periodo".to_string();
+        }
+
+        let report = assess(grades);
+        assert_eq!(report.period, "Secondo periodo");
+        assert!(report.risks.is_empty());
+    }
+}
@@ -0,0 +1,176 @@
+// Module: search
+use crate::response_types::{Agenda, Grade, Lesson};
+use crate::subjects::Subject;
+
+// lowercase and remove the accents, every character is mapped to exactly one character
+fn fold(text: &str) -> Vec<char> {
+    text.chars()
+        .map(|c| {
+            let c = c.to_lowercase().next().unwrap_or(c);
+            match c {
+                'à' | 'á' | 'â' | 'ä' | 'ã' => 'a',
+                'è' | 'é' | 'ê' | 'ë' => 'e',
+                'ì' | 'í' | 'î' | 'ï' => 'i',
+                'ò' | 'ó' | 'ô' | 'ö' | 'õ' => 'o',
+                'ù' | 'ú' | 'û' | 'ü' => 'u',
+                'ç' => 'c',
+                _ => c,
+            }
+        })
+        .collect()
+}
+
+// ranges of characters where a word of the query is found
+fn find_word(text: &[char], word: &[char]) -> Vec<(usize, usize)> {
+    if word.is_empty() || word.len() > text.len() {
+        return Vec::new();
+    }
+    (0..=text.len() - word.len())
+        .filter(|i| text[*i..*i + word.len()] == *word)
+        .map(|i| (i, i + word.len()))
+        .collect()
+}
+
+/*
+Find every word of the query in a text, ignoring case and accents
+None if one of the words is missing, otherwise the ranges of characters to highlight
+*/
+pub fn find_matches(text: &str, query: &str) -> Option<Vec<(usize, usize)>> {
+    let text = fold(text);
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for word in query.split_whitespace() {
+        let found = find_word(&text, &fold(word));
+        if found.is_empty() {
+            return None;
+        }
+        ranges.extend(found);
+    }
+    ranges.sort();
+    Some(ranges)
+}
+
+// surround the matches with the given markers
+pub fn highlight(text: &str, ranges: &[(usize, usize)], start: &str, end: &str) -> String {
+    let mut result = String::new();
+    let mut open = false;
+    for (i, c) in text.chars().enumerate() {
+        let inside = ranges.iter().any(|(from, to)| i >= *from && i < *to);
+        if inside && !open {
+            result.push_str(start);
+            open = true;
+        } else if !inside && open {
+            result.push_str(end);
+            open = false;
+        }
+        result.push(c);
+    }
+    if open {
+        result.push_str(end);
+    }
+    result
+}
+
+pub struct SearchResult {
+    pub date: String,
+    pub source: &'static str,
+    pub subject: String,
+    pub text: String,
+    pub ranges: Vec<(usize, usize)>,
+}
+
+// search the topics of the lessons, the notes of the agenda and the notes of the grades
+pub fn search(
+    query: &str,
+    lessons: &[Lesson],
+    agenda: &[Agenda],
+    grades: &[Grade],
+) -> Vec<SearchResult> {
+    let mut results: Vec<SearchResult> = Vec::new();
+
+    let mut push = |date: &str, source: &'static str, subject: Option<Subject>, text: &str| {
+        if let Some(ranges) = find_matches(text, query) {
+            let date: String = date.chars().take(10).collect();
+            // the same lesson can be recorded by more than one teacher
+            let subject = subject.map(|x| x.name()).unwrap_or_default();
+            if results
+                .iter()
+                .any(|x| x.date == date && x.subject == subject && x.text == text)
+            {
+                return;
+            }
+            results.push(SearchResult {
+                date,
+                source,
+                subject,
+                text: text.to_string(),
+                ranges,
+            });
+        }
+    };
+
+    for lesson in lessons {
+        push(
+            &lesson.evtDate,
+            "lesson",
+            Subject::from_lesson(lesson),
+            &lesson.lessonArg,
+        );
+    }
+    for event in agenda {
+        push(
+            &event.evtDatetimeBegin,
+            "agenda",
+            Subject::from_agenda(event),
+            &event.notes,
+        );
+    }
+    for grade in grades {
+        push(
+            &grade.evtDate,
+            "grade",
+            Some(Subject::new(
+                Some(grade.subjectId),
+                Some(&grade.subjectCode),
+                &grade.subjectDesc,
+            )),
+            &grade.notesForFamily,
+        );
+    }
+
+    results.sort_by(|a, b| a.date.cmp(&b.date));
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fold_removes_case_and_accents() {
+        let folded: String = fold("Perché ÈRA Così").into_iter().collect();
+        assert_eq!(folded, "perche era cosi");
+        // one character for each character, so that the ranges match the original text
+        assert_eq!(fold("àèìòù").len(), "àèìòù".chars().count());
+    }
+
+    #[test]
+    fn find_matches_needs_every_word() {
+        let text = "Verifica di Città e università";
+        assert_eq!(find_matches(text, "citta"), Some(vec![(12, 17)]));
+        assert_eq!(
+            find_matches(text, "UNIVERSITÀ verifica"),
+            Some(vec![(0, 8), (20, 30)])
+        );
+        assert_eq!(find_matches(text, "citta storia"), None);
+    }
+
+    #[test]
+    fn highlight_marks_the_ranges() {
+        let text = "Città e città";
+        let ranges = find_matches(text, "citta").unwrap();
+        assert_eq!(highlight(text, &ranges, "[", "]"), "[Città] e [città]");
+        // adjacent ranges share the markers
+        assert_eq!(highlight("abcd", &[(0, 2), (2, 4)], "[", "]"), "[abcd]");
+        assert_eq!(highlight("abcd", &[], "[", "]"), "abcd");
+    }
+}
@@ -1,24 +1,219 @@
 use crate::api::TokenCredential;
-use config::{Config, File};
-use reqwest::header::{HeaderMap, HeaderValue};
+use config::{Config, ConfigError, File};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
 use serde::{Deserialize, Serialize};
-use std::env::{consts, var};
+use std::collections::HashMap;
+use std::env::consts;
 use std::path::PathBuf;
 use std::sync::Mutex;
 #[derive(Serialize, Deserialize, Debug)]
 pub struct ConfigSettings {
     #[serde(alias = "wrap-width")]
     pub wrap_width: usize,
+    #[serde(alias = "subject-width", default = "default_subject_width")]
+    pub subject_width: usize,
+    #[serde(alias = "average-rule", default)]
+    pub average_rule: AverageRule,
+    // print the name of the profile before the output, useful with more profiles
+    #[serde(alias = "profile-label", default)]
+    pub profile_label: bool,
+}
+
+// how the average of a subject is computed
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum AverageRule {
+    Simple,
+    #[default]
+    Weighted,
+}
+
+// thresholds of the risk command, every field is optional
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct RiskSettings {
+    pub threshold: f64,
+    #[serde(alias = "min-grades")]
+    pub min_grades: usize,
+    #[serde(alias = "trend-length")]
+    pub trend_length: usize,
+    #[serde(alias = "failing-streak")]
+    pub failing_streak: usize,
+    // personal goals keyed by subjectId, subjectCode, description or alias
+    pub goals: HashMap<String, f64>,
+}
+
+// settings of the credit command, every field is optional
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct CreditSettings {
+    // 3, 4 or 5
+    pub year: Option<u8>,
+    // credits obtained in the previous years
+    #[serde(alias = "past-credits")]
+    pub past_credits: Option<u32>,
+    // conduct grade, when it is not among the grades
+    pub conduct: Option<u32>,
+}
+
+// settings of the attendance, every field is optional
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct AttendanceSettings {
+    // hours of lesson of the school year (monte ore)
+    #[serde(alias = "annual-hours")]
+    pub annual_hours: u32,
+    // maximum share of hours that can be missed, in percentage
+    pub limit: f64,
+    // share of missed hours after which a warning is shown, in percentage
+    pub warning: f64,
+    // hours of lesson of each day of the week, from monday to sunday
+    #[serde(alias = "daily-hours")]
+    pub daily_hours: Vec<u32>,
+}
+
+impl Default for AttendanceSettings {
+    fn default() -> Self {
+        AttendanceSettings {
+            annual_hours: 1056,
+            limit: 25.0,
+            warning: 20.0,
+            daily_hours: vec![6, 6, 6, 6, 6, 0, 0],
+        }
+    }
+}
+
+// settings of the weekly timetable, every field is optional
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct TimetableSettings {
+    // weeks of lessons used to infer the timetable
+    pub weeks: u32,
+    // start time(HH:MM) of every hour position
+    pub hours: Vec<String>,
+    // length of an hour of lesson in minutes
+    #[serde(alias = "hour-length")]
+    pub hour_length: u32,
+    // subjects set by hand, keyed by weekday(monday, tuesday, ...) and hour position
+    #[serde(alias = "override")]
+    pub overrides: HashMap<String, HashMap<String, String>>,
+}
+
+impl Default for TimetableSettings {
+    fn default() -> Self {
+        TimetableSettings {
+            weeks: 4,
+            hours: [
+                "08:00", "09:00", "10:00", "11:00", "12:00", "13:00", "14:00",
+            ]
+            .iter()
+            .map(|x| x.to_string())
+            .collect(),
+            hour_length: 60,
+            overrides: HashMap::new(),
+        }
+    }
+}
+
+// settings of the detection of the tests, every field is optional
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct AssessmentSettings {
+    // words used by the teachers to announce a test
+    pub keywords: Vec<String>,
+    // days of the agenda looked up by the exams command
+    pub days: u32,
+}
+
+impl Default for AssessmentSettings {
+    fn default() -> Self {
+        AssessmentSettings {
+            keywords: [
+                "verifica",
+                "verifiche",
+                "compito in classe",
+                "interrogazione",
+                "interrogazioni",
+                "test",
+                "prova",
+            ]
+            .iter()
+            .map(|x| x.to_string())
+            .collect(),
+            days: 30,
+        }
+    }
+}
+
+// settings of the study planner, every field is optional
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct PlannerSettings {
+    // length of a revision session in minutes
+    #[serde(alias = "session-length")]
+    pub session_length: u32,
+    // sessions for a test of a subject with a good average
+    pub sessions: u32,
+    // days before a test in which the sessions are placed
+    pub days: u32,
+    // free time slots(HH:MM-HH:MM) keyed by weekday(monday, tuesday, ...)
+    #[serde(alias = "free-time")]
+    pub free_time: HashMap<String, Vec<String>>,
+}
+
+impl Default for PlannerSettings {
+    fn default() -> Self {
+        PlannerSettings {
+            session_length: 45,
+            sessions: 2,
+            days: 7,
+            free_time: ["monday", "tuesday", "wednesday", "thursday", "friday"]
+                .iter()
+                .map(|x| (x.to_string(), vec!["15:00-17:00".to_string()]))
+                .collect(),
+        }
+    }
+}
+
+impl Default for RiskSettings {
+    fn default() -> Self {
+        RiskSettings {
+            threshold: 6.0,
+            min_grades: 2,
+            trend_length: 3,
+            failing_streak: 2,
+            goals: HashMap::new(),
+        }
+    }
+}
+
+fn default_subject_width() -> usize {
+    20
 }
 
 pub struct UserConfig {
     pub raw_body: Config,
     pub default_headers: HeaderMap,
     pub user_settings: ConfigSettings,
+    // aliases of the subjects keyed by subjectId, subjectCode or description
+    pub subject_aliases: HashMap<String, String>,
+    pub risk_settings: RiskSettings,
+    pub credit_settings: CreditSettings,
+    pub attendance_settings: AttendanceSettings,
+    pub timetable_settings: TimetableSettings,
+    pub assessment_settings: AssessmentSettings,
+    pub planner_settings: PlannerSettings,
     // .credntials.json file and config.toml file
     pub paths: (PathBuf, PathBuf),
 }
 
+impl UserConfig {
+    // files stored by terminalviva live next to config.toml
+    pub fn data_path(&self, file_name: &str) -> PathBuf {
+        self.paths.1.with_file_name(file_name)
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 struct DefaultHeaders {
     pub key: String,
@@ -33,8 +228,19 @@ fn allow_list() {
     };
 }
 
+// the tests read the example configuration of the repository
+#[cfg(test)]
+fn get_config_path() -> (PathBuf, PathBuf) {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    (
+        dir.join(".credentials.json"),
+        dir.join("config.example.toml"),
+    )
+}
+
+#[cfg(not(test))]
 fn get_config_path() -> (PathBuf, PathBuf) {
-    let mut config_dir: PathBuf = match var("HOME") {
+    let mut config_dir: PathBuf = match std::env::var("HOME") {
         Ok(v) => PathBuf::from(v),
         Err(_) => panic!("error at getting $HOME"),
     };
@@ -78,6 +284,89 @@ fn get_user_settings(config: &Config) -> ConfigSettings {
     config_settings
 }
 
+fn get_subject_aliases(config: &Config) -> HashMap<String, String> {
+    // the [subjects] table is optional
+    let subject_aliases: HashMap<String, String> = match config.get("subjects") {
+        Ok(v) => v,
+        Err(ConfigError::NotFound(_)) => HashMap::new(),
+        Err(e) => panic!("error at parsing subject aliases: {}", e),
+    };
+
+    subject_aliases
+}
+
+fn get_risk_settings(config: &Config) -> RiskSettings {
+    // the [risk] table is optional
+    let risk_settings: RiskSettings = match config.get("risk") {
+        Ok(v) => v,
+        Err(ConfigError::NotFound(_)) => RiskSettings::default(),
+        Err(e) => panic!("error at parsing risk settings: {}", e),
+    };
+
+    risk_settings
+}
+
+fn get_credit_settings(config: &Config) -> CreditSettings {
+    // the [credit] table is optional
+    let credit_settings: CreditSettings = match config.get("credit") {
+        Ok(v) => v,
+        Err(ConfigError::NotFound(_)) => CreditSettings::default(),
+        Err(e) => panic!("error at parsing credit settings: {}", e),
+    };
+
+    if let Some(year) = credit_settings.year {
+        if !(3..=5).contains(&year) {
+            panic!("error at parsing credit settings: year must be 3, 4 or 5");
+        }
+    }
+
+    credit_settings
+}
+
+fn get_attendance_settings(config: &Config) -> AttendanceSettings {
+    // the [attendance] table is optional
+    let attendance_settings: AttendanceSettings = match config.get("attendance") {
+        Ok(v) => v,
+        Err(ConfigError::NotFound(_)) => AttendanceSettings::default(),
+        Err(e) => panic!("error at parsing attendance settings: {}", e),
+    };
+
+    attendance_settings
+}
+
+fn get_timetable_settings(config: &Config) -> TimetableSettings {
+    // the [timetable] table is optional
+    let timetable_settings: TimetableSettings = match config.get("timetable") {
+        Ok(v) => v,
+        Err(ConfigError::NotFound(_)) => TimetableSettings::default(),
+        Err(e) => panic!("error at parsing timetable settings: {}", e),
+    };
+
+    timetable_settings
+}
+
+fn get_assessment_settings(config: &Config) -> AssessmentSettings {
+    // the [assessments] table is optional
+    let assessment_settings: AssessmentSettings = match config.get("assessments") {
+        Ok(v) => v,
+        Err(ConfigError::NotFound(_)) => AssessmentSettings::default(),
+        Err(e) => panic!("error at parsing assessment settings: {}", e),
+    };
+
+    assessment_settings
+}
+
+fn get_planner_settings(config: &Config) -> PlannerSettings {
+    // the [planner] table is optional
+    let planner_settings: PlannerSettings = match config.get("planner") {
+        Ok(v) => v,
+        Err(ConfigError::NotFound(_)) => PlannerSettings::default(),
+        Err(e) => panic!("error at parsing planner settings: {}", e),
+    };
+
+    planner_settings
+}
+
 fn get_default_headers(config: &Config) -> HeaderMap {
     // get default headers
     let mut headers = HeaderMap::new();
@@ -95,8 +384,10 @@ fn get_default_headers(config: &Config) -> HeaderMap {
             Ok(v) => v,
         };
 
-        // convert key from &str to &'static str
-        let key: &'static str = unsafe { std::mem::transmute(pair.key.as_bytes()) };
+        let key: HeaderName = match HeaderName::from_bytes(pair.key.as_bytes()) {
+            Err(_) => continue,
+            Ok(v) => v,
+        };
         headers.insert(key, value);
     }
 
@@ -107,12 +398,26 @@ pub fn get_config() -> UserConfig {
     let config = get_raw_config();
     let default_headers = get_default_headers(&config);
     let user_settings = get_user_settings(&config);
+    let subject_aliases = get_subject_aliases(&config);
+    let risk_settings = get_risk_settings(&config);
+    let credit_settings = get_credit_settings(&config);
+    let attendance_settings = get_attendance_settings(&config);
+    let timetable_settings = get_timetable_settings(&config);
+    let assessment_settings = get_assessment_settings(&config);
+    let planner_settings = get_planner_settings(&config);
 
     UserConfig {
         raw_body: config,
         paths: get_config_path(),
         default_headers,
         user_settings,
+        subject_aliases,
+        risk_settings,
+        credit_settings,
+        attendance_settings,
+        timetable_settings,
+        assessment_settings,
+        planner_settings,
     }
 }
 
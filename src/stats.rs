@@ -0,0 +1,157 @@
+// Module: stats
+use crate::response_types::Grade;
+use crate::settings::AverageRule;
+use crate::USER_CONFIG;
+use std::collections::HashMap;
+
+// grades below this value are failing grades
+pub const PASSING_GRADE: f64 = 6.0;
+
+pub struct Summary {
+    pub count: usize,
+    pub average: f64,
+    pub median: f64,
+    pub std_dev: f64,
+    pub min: f64,
+    pub max: f64,
+    pub failing: usize,
+}
+
+impl Summary {
+    // return None if there are no values
+    pub fn from_values(values: &[f64]) -> Option<Self> {
+        if values.is_empty() {
+            return None;
+        }
+
+        Some(Summary {
+            count: values.len(),
+            average: average(values),
+            median: median(values),
+            std_dev: std_dev(values),
+            min: values.iter().cloned().fold(f64::INFINITY, f64::min),
+            max: values.iter().cloned().fold(f64::NEG_INFINITY, f64::max),
+            failing: values.iter().filter(|x| **x < PASSING_GRADE).count(),
+        })
+    }
+
+    // share of failing grades in percentage
+    pub fn failing_share(&self) -> f64 {
+        self.failing as f64 / self.count as f64 * 100.0
+    }
+}
+
+pub fn average(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+pub fn weighted_average(values: &[(f64, f64)]) -> f64 {
+    let mut sum: f64 = 0.0;
+    let mut weights: f64 = 0.0;
+    for (value, weight) in values {
+        sum += value * weight;
+        weights += weight;
+    }
+    sum / weights
+}
+
+// average of (value, weight) pairs following the average-rule of config.toml
+pub fn rule_average(values: &[(f64, f64)]) -> f64 {
+    match USER_CONFIG.user_settings.average_rule {
+        AverageRule::Simple => {
+            let values: Vec<f64> = values.iter().map(|(value, _)| *value).collect();
+            average(&values)
+        }
+        AverageRule::Weighted => weighted_average(values),
+    }
+}
+
+pub fn median(values: &[f64]) -> f64 {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+
+    let middle = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        (sorted[middle - 1] + sorted[middle]) / 2.0
+    } else {
+        sorted[middle]
+    }
+}
+
+// population standard deviation
+pub fn std_dev(values: &[f64]) -> f64 {
+    let mean = average(values);
+    let variance = values.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / values.len() as f64;
+    variance.sqrt()
+}
+
+// count the grades for each integer value from 1 to 10, e.g. 6.5 belongs to 6
+pub fn histogram(values: &[f64]) -> [usize; 10] {
+    let mut buckets = [0; 10];
+    for value in values {
+        let index = (value.floor() as usize).clamp(1, 10) - 1;
+        buckets[index] += 1;
+    }
+    buckets
+}
+
+// average of the current period of every subject, keyed by subjectId
+pub fn current_averages(grades: &[Grade]) -> HashMap<u32, f64> {
+    // the current period is the latest one with a grade
+    let current_period = grades.iter().map(|x| x.periodPos).max().unwrap_or(0);
+    let mut by_subject: HashMap<u32, Vec<(f64, f64)>> = HashMap::new();
+    for grade in grades
+        .iter()
+        .filter(|x| !x.canceled && x.periodPos == current_period)
+    {
+        by_subject
+            .entry(grade.subjectId)
+            .or_default()
+            .push((grade.decimalValue, grade.weightFactor));
+    }
+
+    by_subject
+        .into_iter()
+        .map(|(subject, values)| (subject, rule_average(&values)))
+        .filter(|(_, average)| !average.is_nan())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::response_types::fixtures::grade;
+
+    #[test]
+    fn median_of_odd_and_even_lengths() {
+        assert_eq!(median(&[7.0, 5.0, 9.0]), 7.0);
+        assert_eq!(median(&[8.0, 4.0, 6.0, 10.0]), 7.0);
+        assert_eq!(median(&[6.5]), 6.5);
+    }
+
+    #[test]
+    fn std_dev_is_the_population_one() {
+        assert_eq!(std_dev(&[6.0, 6.0, 6.0]), 0.0);
+        assert_eq!(std_dev(&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]), 2.0);
+    }
+
+    #[test]
+    fn current_averages_of_the_latest_period() {
+        let mut oral = grade(1, "MATEMATICA", "2026-02-10", 8.0);
+        oral.weightFactor = 3.0;
+        let mut canceled = grade(1, "MATEMATICA", "2026-02-11", 2.0);
+        canceled.canceled = true;
+        let mut grades = vec![
+            grade(2, "FISICA", "2025-10-06", 4.0),
+            grade(1, "MATEMATICA", "2026-02-09", 4.0),
+            oral,
+            canceled,
+        ];
+        for grade in &mut grades[1..] {
+            grade.periodPos = 2;
+        }
+
+        // weighted following config.example.toml, FISICA has no grades in this period
+        assert_eq!(current_averages(&grades), HashMap::from([(1, 7.0)]));
+    }
+}
@@ -0,0 +1,508 @@
+// Module: subjects
+use crate::response_types::{Agenda, Lesson};
+use crate::USER_CONFIG;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// words that can be dropped when a subject name has to be shortened
+const STOP_WORDS: [&str; 22] = [
+    "e", "ed", "di", "del", "dello", "della", "dei", "degli", "delle", "la", "le", "il", "lo",
+    "gli", "a", "ad", "al", "alla", "in", "per", "con", "su",
+];
+
+// normalize a key or a name so that the comparison ignores case and extra whitespaces
+pub fn normalize(name: &str) -> String {
+    name.split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/*
+Look up a table of config.toml keyed by subject
+the table can be keyed by subjectId, subjectCode or by the description of the subject
+*/
+pub fn find_by_subject<'a, V>(
+    table: &'a HashMap<String, V>,
+    id: Option<u32>,
+    code: Option<&str>,
+    desc: &str,
+) -> Option<&'a V> {
+    if table.is_empty() {
+        return None;
+    }
+
+    let mut keys: Vec<String> = Vec::new();
+    if let Some(id) = id {
+        keys.push(id.to_string());
+    }
+    if let Some(code) = code {
+        keys.push(normalize(code));
+    }
+    keys.push(normalize(desc));
+    // "... sez. INFORMATICA" can be looked up without the section as well
+    keys.push(normalize(&strip_section(desc)));
+
+    keys.iter().find_map(|key| {
+        table
+            .iter()
+            .find(|(table_key, _)| normalize(table_key) == *key)
+            .map(|(_, value)| value)
+    })
+}
+
+// look for an alias defined in the [subjects] table of config.toml
+pub fn find_alias(id: Option<u32>, code: Option<&str>, desc: &str) -> Option<String> {
+    find_by_subject(&USER_CONFIG.subject_aliases, id, code, desc).cloned()
+}
+
+// get the name of the subject to display, either the configured alias or an abbreviation
+pub fn subject_name(id: Option<u32>, code: Option<&str>, desc: &str) -> String {
+    match find_alias(id, code, desc) {
+        Some(alias) => alias,
+        None => abbreviate(desc, USER_CONFIG.user_settings.subject_width),
+    }
+}
+
+// cut a word after a consonant, e.g. "MOTORIE" -> "MOT."
+fn shorten_word(word: &str, max_chars: usize) -> String {
+    if word.chars().count() <= max_chars + 1 {
+        return word.to_string();
+    }
+
+    let mut shortened: String = word.chars().take(max_chars).collect();
+    while shortened.chars().count() > 1
+        && shortened
+            .chars()
+            .last()
+            .is_some_and(|c| "aeiouàèéìòùAEIOUÀÈÉÌÒÙ".contains(c))
+    {
+        shortened.pop();
+    }
+
+    format!("{}.", shortened)
+}
+
+// remove the section suffix ("sez. INFORMATICA") and the parenthesis from a subject name
+fn strip_section(desc: &str) -> String {
+    let mut words: Vec<&str> = Vec::new();
+    let mut in_parenthesis = false;
+    for word in desc.split_whitespace() {
+        if word.to_lowercase().starts_with("sez.") {
+            break;
+        }
+        if word.starts_with('(') {
+            in_parenthesis = true;
+        }
+        if !in_parenthesis {
+            words.push(word);
+        }
+        if word.ends_with(')') {
+            in_parenthesis = false;
+        }
+    }
+
+    words
+        .join(" ")
+        .trim_end_matches([',', '-', ' '])
+        .to_string()
+}
+
+/*
+Shorten a subject name so that it fits in max_width characters
+1. the section suffix ("sez. INFORMATICA") and the parenthesis are removed
+2. a list of subjects ("STORIA,CITTADINANZA E COSTITUZIONE") is reduced to the first one
+3. the stop words are dropped and the words get shorter, the first one is kept as long as possible
+4. if nothing of the above works, the name becomes an acronym ("TPSIT")
+*/
+pub fn abbreviate(desc: &str, max_width: usize) -> String {
+    let name = strip_section(desc);
+    let name = name.as_str();
+    if name.chars().count() <= max_width {
+        return name.to_string();
+    }
+
+    if let Some((first, _)) = name.split_once(',') {
+        let first = first.trim();
+        if !first.is_empty() && first.chars().count() <= max_width {
+            return first.to_string();
+        }
+    }
+
+    let significant: Vec<&str> = name
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|word| !word.is_empty())
+        .filter(|word| !STOP_WORDS.contains(&word.to_lowercase().as_str()))
+        .collect();
+
+    let fits = |words: &Vec<String>| words.join(" ").chars().count() <= max_width;
+
+    let mut candidate: Vec<String> = significant.iter().map(|x| x.to_string()).collect();
+    if fits(&candidate) {
+        return candidate.join(" ");
+    }
+
+    // shorten every word but the first one
+    for max_chars in (3..=6).rev() {
+        candidate = significant
+            .iter()
+            .enumerate()
+            .map(|(i, word)| {
+                if i == 0 {
+                    word.to_string()
+                } else {
+                    shorten_word(word, max_chars)
+                }
+            })
+            .collect();
+        if fits(&candidate) {
+            return candidate.join(" ");
+        }
+    }
+
+    // shorten the first word as well
+    for max_chars in (3..=6).rev() {
+        candidate = significant
+            .iter()
+            .map(|word| shorten_word(word, max_chars))
+            .collect();
+        if fits(&candidate) {
+            return candidate.join(" ");
+        }
+    }
+
+    if significant.len() > 1 {
+        let acronym: String = significant
+            .iter()
+            .filter_map(|word| word.chars().next())
+            .flat_map(|c| c.to_uppercase())
+            .collect();
+        if acronym.chars().count() <= max_width {
+            return acronym;
+        }
+    }
+
+    // the last resort is a hard cut
+    let cut: String = name.chars().take(max_width.saturating_sub(1)).collect();
+    format!("{}.", cut.trim_end())
+}
+
+// a subject found in the records, used to resolve the --name option
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Subject {
+    pub id: Option<u32>,
+    pub code: Option<String>,
+    pub desc: String,
+}
+
+impl Subject {
+    pub fn new(id: Option<u32>, code: Option<&str>, desc: &str) -> Self {
+        Subject {
+            id,
+            code: code.map(|x| x.to_string()),
+            desc: desc.to_string(),
+        }
+    }
+
+    pub fn from_lesson(lesson: &Lesson) -> Option<Self> {
+        lesson.subjectDesc.as_ref().map(|desc| {
+            Subject::new(
+                lesson.subjectId,
+                lesson.subjectCode.as_ref().and_then(|x| x.as_str()),
+                desc,
+            )
+        })
+    }
+
+    pub fn from_agenda(agenda: &Agenda) -> Option<Self> {
+        agenda
+            .subjectDesc
+            .as_ref()
+            .map(|desc| Subject::new(agenda.subjectId, None, desc))
+    }
+
+    pub fn name(&self) -> String {
+        subject_name(self.id, self.code.as_deref(), &self.desc)
+    }
+
+    /*
+    Rank how well a term matches this subject, the lower the better
+    0: id, code, description or alias
+    1: prefix of the description or of the alias
+    2: prefix of one of the words
+    3: substring
+    */
+    fn match_rank(&self, term: &str) -> Option<u8> {
+        let desc = normalize(&self.desc);
+        let stripped = normalize(&strip_section(&self.desc));
+        let name = normalize(&self.name());
+        let alias = find_alias(self.id, self.code.as_deref(), &self.desc).map(|x| normalize(&x));
+        let code = self.code.as_deref().map(normalize);
+        let id = self.id.map(|x| x.to_string());
+
+        let exact = [
+            Some(desc.clone()),
+            Some(stripped),
+            Some(name.clone()),
+            alias,
+            code,
+            id,
+        ];
+        if exact.iter().flatten().any(|x| x == term) {
+            return Some(0);
+        }
+        if desc.starts_with(term) || name.starts_with(term) {
+            return Some(1);
+        }
+        let is_word_prefix = |x: &str| {
+            x.split(|c: char| !c.is_alphanumeric())
+                .any(|word| word.starts_with(term))
+        };
+        if is_word_prefix(&desc) || is_word_prefix(&name) {
+            return Some(2);
+        }
+        if desc.contains(term) || name.contains(term) {
+            return Some(3);
+        }
+        None
+    }
+}
+
+/*
+Select the subjects matching the --name option, which can be a comma separated list
+Every term selects the subjects with the best match
+A term matching no subject, or more than one subject partially, is an error to be reported
+*/
+pub fn select_subjects(name: &str, subjects: &[Subject]) -> Result<Vec<Subject>, String> {
+    let mut selected: Vec<Subject> = Vec::new();
+
+    for term in name.split(',').map(normalize).filter(|x| !x.is_empty()) {
+        let ranked: Vec<(u8, &Subject)> = subjects
+            .iter()
+            .filter_map(|x| x.match_rank(&term).map(|rank| (rank, x)))
+            .collect();
+
+        let best = match ranked.iter().map(|(rank, _)| *rank).min() {
+            Some(v) => v,
+            None => return Err(format!("No subject matches \"{}\"", term)),
+        };
+
+        let candidates: Vec<&Subject> = ranked
+            .into_iter()
+            .filter(|(rank, _)| *rank == best)
+            .map(|(_, x)| x)
+            .collect();
+
+        // an exact match is never ambiguous, e.g. two subjects sharing the same alias
+        if best > 0 && candidates.len() > 1 {
+            let mut message = format!("The subject \"{}\" is ambiguous, candidates:", term);
+            for candidate in candidates {
+                message.push_str(&format!(
+                    "\n  {} ({})",
+                    candidate.name(),
+                    candidate.desc.trim()
+                ));
+            }
+            return Err(message);
+        }
+
+        selected.extend(candidates.into_iter().cloned());
+    }
+
+    Ok(selected)
+}
+
+/*
+Resolve the --name option once against the subjects of every record, None without --name
+the commands reading more sources resolve the name against all of them together
+*/
+pub fn resolve_name(
+    name: &Option<String>,
+    subjects: impl IntoIterator<Item = Subject>,
+) -> Result<Option<Vec<Subject>>, String> {
+    let name = match name {
+        Some(v) => v,
+        None => return Ok(None),
+    };
+
+    // the distinct subjects, the same one is sent by every record
+    let mut distinct: Vec<Subject> = Vec::new();
+    for subject in subjects {
+        if !distinct
+            .iter()
+            .any(|x| normalize(&x.desc) == normalize(&subject.desc))
+        {
+            distinct.push(subject);
+        }
+    }
+
+    select_subjects(name, &distinct).map(Some)
+}
+
+// keep the records of the selected subjects, every record without a selection
+pub fn filter_by_subjects<T>(
+    records: Vec<T>,
+    selected: &Option<Vec<Subject>>,
+    subject_of: impl Fn(&T) -> Option<Subject>,
+) -> Vec<T> {
+    let selected = match selected {
+        Some(v) => v,
+        None => return records,
+    };
+
+    records
+        .into_iter()
+        .filter(|record| match subject_of(record) {
+            Some(subject) => selected
+                .iter()
+                .any(|x| normalize(&x.desc) == normalize(&subject.desc)),
+            None => false,
+        })
+        .collect()
+}
+
+// filter records by the --name option, subject_of gets the subject of a record if any
+pub fn filter_by_name<T>(
+    records: Vec<T>,
+    name: &Option<String>,
+    subject_of: impl Fn(&T) -> Option<Subject>,
+) -> Result<Vec<T>, String> {
+    let selected = resolve_name(name, records.iter().filter_map(&subject_of))?;
+    Ok(filter_by_subjects(records, &selected, subject_of))
+}
+
+/*
+Subjects taught by every teacher, used for the events of the agenda without a subject
+only the teachers with a single subject are kept
+*/
+pub fn subjects_by_teacher(lessons: &[Lesson]) -> HashMap<String, Subject> {
+    let mut subjects: HashMap<String, Vec<Subject>> = HashMap::new();
+    for lesson in lessons {
+        let subject = match Subject::from_lesson(lesson) {
+            Some(v) => v,
+            None => continue,
+        };
+        let taught = subjects.entry(normalize(&lesson.authorName)).or_default();
+        if !taught
+            .iter()
+            .any(|x| normalize(&x.desc) == normalize(&subject.desc))
+        {
+            taught.push(subject);
+        }
+    }
+
+    subjects
+        .into_iter()
+        .filter(|(_, taught)| taught.len() == 1)
+        .map(|(teacher, mut taught)| (teacher, taught.remove(0)))
+        .collect()
+}
+
+// the subject of an event of the agenda, or the one taught by its author
+pub fn agenda_subject(agenda: &Agenda, teachers: &HashMap<String, Subject>) -> Option<Subject> {
+    Subject::from_agenda(agenda).or_else(|| teachers.get(&normalize(&agenda.authorName)).cloned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subjects() -> Vec<Subject> {
+        vec![
+            Subject::new(Some(1), None, "LINGUA E LETTERATURA ITALIANA"),
+            Subject::new(Some(2), None, "MATEMATICA"),
+            Subject::new(Some(3), None, "MATEMATICA E COMPLEMENTI DI MATEMATICA"),
+            Subject::new(Some(4), None, "STORIA,CITTADINANZA E COSTITUZIONE"),
+        ]
+    }
+
+    fn ids(subjects: Result<Vec<Subject>, String>) -> Vec<u32> {
+        subjects.unwrap().into_iter().filter_map(|x| x.id).collect()
+    }
+
+    #[test]
+    fn abbreviate_keeps_short_names() {
+        assert_eq!(abbreviate("MATEMATICA", 20), "MATEMATICA");
+        assert_eq!(
+            abbreviate("INFORMATICA sez. INFORMATICA", 20),
+            "INFORMATICA"
+        );
+        assert_eq!(abbreviate("FISICA (LABORATORIO)", 20), "FISICA");
+    }
+
+    #[test]
+    fn abbreviate_shortens_long_names() {
+        assert_eq!(
+            abbreviate("STORIA,CITTADINANZA E COSTITUZIONE", 20),
+            "STORIA"
+        );
+        assert_eq!(
+            abbreviate("LINGUA E LETTERATURA ITALIANA", 20),
+            "LINGUA LETTER. ITAL."
+        );
+        assert_eq!(
+            abbreviate(
+                "TECNOLOGIE E PROGETTAZIONE DI SISTEMI INFORMATICI E DI TELECOMUNICAZIONI",
+                5
+            ),
+            "TPSIT"
+        );
+        assert_eq!(abbreviate("MATEMATICA", 2), "M.");
+    }
+
+    #[test]
+    fn match_rank_orders_matches() {
+        let italian = &subjects()[0];
+        assert_eq!(italian.match_rank("1"), Some(0));
+        // alias of config.example.toml
+        assert_eq!(italian.match_rank("italiano"), Some(0));
+        assert_eq!(italian.match_rank("lingua"), Some(1));
+        assert_eq!(italian.match_rank("letter"), Some(2));
+        assert_eq!(italian.match_rank("ettera"), Some(3));
+        assert_eq!(italian.match_rank("storia"), None);
+    }
+
+    #[test]
+    fn select_subjects_prefers_exact_matches() {
+        assert_eq!(ids(select_subjects("matematica", &subjects())), vec![2]);
+        assert_eq!(ids(select_subjects("3", &subjects())), vec![3]);
+    }
+
+    #[test]
+    fn select_subjects_with_a_list() {
+        assert_eq!(
+            ids(select_subjects("ital, storia", &subjects())),
+            vec![1, 4]
+        );
+        assert_eq!(ids(select_subjects("compl,", &subjects())), vec![3]);
+        assert!(ids(select_subjects(" , ", &subjects())).is_empty());
+    }
+
+    #[test]
+    fn select_subjects_reports_unknown_and_ambiguous_terms() {
+        assert_eq!(
+            select_subjects("matematica,chimica", &subjects()).unwrap_err(),
+            "No subject matches \"chimica\""
+        );
+        let error = select_subjects("mat", &subjects()).unwrap_err();
+        assert!(error.starts_with("The subject \"mat\" is ambiguous"));
+        assert!(error.contains("(MATEMATICA E COMPLEMENTI DI MATEMATICA)"));
+    }
+
+    #[test]
+    fn resolve_name_uses_every_source() {
+        let lessons = vec![Subject::new(Some(2), None, "MATEMATICA")];
+        let grades = vec![Subject::new(Some(5), None, "FISICA"), lessons[0].clone()];
+        let selected = resolve_name(
+            &Some("fisica,matematica".to_string()),
+            lessons.iter().chain(grades.iter()).cloned(),
+        )
+        .unwrap();
+        assert_eq!(selected.as_ref().map(|x| x.len()), Some(2));
+
+        // the lessons alone keep the records of the subjects found elsewhere
+        let kept = filter_by_subjects(lessons, &selected, |x| Some(x.clone()));
+        assert_eq!(kept.len(), 1);
+        assert!(resolve_name(&None, grades).unwrap().is_none());
+    }
+}
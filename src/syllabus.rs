@@ -0,0 +1,141 @@
+// Module: syllabus
+use crate::response_types::Lesson;
+use crate::subjects::{normalize, Subject};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+// a topic covered in one or more adjacent lessons
+pub struct Topic {
+    pub first_date: String,
+    pub last_date: String,
+    pub hours: u32,
+    pub topic: String,
+}
+
+/*
+Collect the topics of the lessons for each subject in chronological order
+the same lesson recorded by more than one teacher is counted once
+and the adjacent lessons with the same topic are merged together
+*/
+pub fn topics_by_subject<'a>(
+    lessons: impl IntoIterator<Item = &'a Lesson>,
+) -> BTreeMap<String, Vec<Topic>> {
+    let mut lessons: Vec<&Lesson> = lessons.into_iter().collect();
+    lessons.sort_by(|a, b| a.evtDate.cmp(&b.evtDate).then(a.evtHPos.cmp(&b.evtHPos)));
+
+    let mut counted: HashSet<(String, u32, String)> = HashSet::new();
+    let mut result: BTreeMap<String, Vec<Topic>> = BTreeMap::new();
+    for lesson in lessons {
+        let subject = match Subject::from_lesson(lesson) {
+            Some(v) => v.name(),
+            None => continue,
+        };
+        let topic = lesson.lessonArg.trim();
+        if topic.is_empty()
+            || !counted.insert((lesson.evtDate.clone(), lesson.evtHPos, subject.clone()))
+        {
+            continue;
+        }
+
+        let topics = result.entry(subject).or_default();
+        let hours = lesson.evtDuration.max(1);
+        match topics.last_mut() {
+            Some(last) if normalize(&last.topic) == normalize(topic) => {
+                last.last_date = lesson.evtDate.clone();
+                last.hours += hours;
+            }
+            _ => topics.push(Topic {
+                first_date: lesson.evtDate.clone(),
+                last_date: lesson.evtDate.clone(),
+                hours,
+                topic: topic.to_string(),
+            }),
+        }
+    }
+
+    result
+}
+
+impl Topic {
+    pub fn dates(&self) -> String {
+        if self.first_date == self.last_date {
+            self.first_date.clone()
+        } else {
+            format!("{} - {}", self.first_date, self.last_date)
+        }
+    }
+}
+
+// hours of lesson of every subject, keyed by subjectId, counting once the lessons with more teachers
+pub fn hours_by_subject(lessons: &[Lesson]) -> HashMap<u32, u32> {
+    let mut counted: HashSet<(String, u32, u32)> = HashSet::new();
+    let mut hours: HashMap<u32, u32> = HashMap::new();
+    for lesson in lessons {
+        let id = match lesson.subjectId {
+            Some(v) => v,
+            None => continue,
+        };
+        for position in lesson.evtHPos..lesson.evtHPos + lesson.evtDuration.max(1) {
+            if counted.insert((lesson.evtDate.clone(), position, id)) {
+                *hours.entry(id).or_default() += 1;
+            }
+        }
+    }
+    hours
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::response_types::fixtures::lesson;
+
+    #[test]
+    fn topics_merge_the_adjacent_lessons() {
+        let mut copresence = lesson("2025-10-06", 1, Some((1, "MATEMATICA")), "Derivate");
+        copresence.authorName = "BIANCHI ANNA".to_string();
+        let mut long = lesson("2025-10-08", 3, Some((1, "MATEMATICA")), "derivate ");
+        long.evtDuration = 2;
+        let lessons = vec![
+            lesson("2025-10-10", 1, Some((1, "MATEMATICA")), "Derivate"),
+            long,
+            lesson("2025-10-06", 1, Some((1, "MATEMATICA")), "Derivate"),
+            copresence,
+            lesson("2025-10-09", 2, Some((1, "MATEMATICA")), "Integrali"),
+            lesson("2025-10-07", 1, Some((2, "FISICA")), ""),
+            lesson("2025-10-07", 2, None, "Assemblea"),
+        ];
+
+        let syllabus = topics_by_subject(&lessons);
+        // lessons without a topic or a subject are left out
+        assert_eq!(syllabus.keys().collect::<Vec<_>>(), vec!["MATEMATICA"]);
+        let topics: Vec<(String, u32, &str)> = syllabus["MATEMATICA"]
+            .iter()
+            .map(|x| (x.dates(), x.hours, x.topic.as_str()))
+            .collect();
+        assert_eq!(
+            topics,
+            vec![
+                ("2025-10-06 - 2025-10-08".to_string(), 3, "Derivate"),
+                ("2025-10-09".to_string(), 1, "Integrali"),
+                // the same topic after another one starts again
+                ("2025-10-10".to_string(), 1, "Derivate"),
+            ]
+        );
+    }
+
+    #[test]
+    fn hours_count_once_the_lessons_with_more_teachers() {
+        let mut long = lesson("2025-10-06", 1, Some((1, "MATEMATICA")), "Derivate");
+        long.evtDuration = 2;
+        let mut copresence = lesson("2025-10-06", 2, Some((1, "MATEMATICA")), "Derivate");
+        copresence.authorName = "BIANCHI ANNA".to_string();
+        let lessons = vec![
+            long,
+            copresence,
+            lesson("2025-10-07", 2, Some((1, "MATEMATICA")), "Integrali"),
+            lesson("2025-10-07", 3, Some((2, "FISICA")), "Moto"),
+            lesson("2025-10-07", 4, None, "Assemblea"),
+        ];
+
+        assert_eq!(hours_by_subject(&lessons), HashMap::from([(1, 3), (2, 1)]));
+    }
+}
@@ -0,0 +1,311 @@
+// Module: timetable
+use crate::response_types::Lesson;
+use crate::subjects::{normalize, Subject};
+use crate::USER_CONFIG;
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+const TIMETABLE_FILE: &str = "timetable.json";
+
+pub const WEEKDAYS: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+
+// weekday name used as key of the overrides in config.toml
+pub fn weekday_key(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "monday",
+        Weekday::Tue => "tuesday",
+        Weekday::Wed => "wednesday",
+        Weekday::Thu => "thursday",
+        Weekday::Fri => "friday",
+        Weekday::Sat => "saturday",
+        Weekday::Sun => "sunday",
+    }
+}
+
+/*
+Subject of every hour position, for every day of the week(0 is monday)
+the subjects are stored as sent by the server, the aliases of config.toml are applied when displayed
+*/
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Timetable {
+    pub days: BTreeMap<u32, BTreeMap<u32, Subject>>,
+}
+
+impl Timetable {
+    /*
+    Infer the weekly timetable from the lessons
+    every hour of every weekday gets the subject recorded most often in that hour
+    */
+    pub fn from_lessons(lessons: &[Lesson]) -> Self {
+        // keyed by description, the same subject may be sent with or without its id
+        let mut counts: HashMap<(u32, u32), HashMap<String, (Subject, u32)>> = HashMap::new();
+        for lesson in lessons {
+            let date = match NaiveDate::parse_from_str(&lesson.evtDate, "%Y-%m-%d") {
+                Ok(v) => v,
+                Err(_) => continue,
+            };
+            let subject = match Subject::from_lesson(lesson) {
+                Some(v) => v,
+                None => continue,
+            };
+
+            let weekday = date.weekday().num_days_from_monday();
+            for position in lesson.evtHPos..lesson.evtHPos + lesson.evtDuration.max(1) {
+                counts
+                    .entry((weekday, position))
+                    .or_default()
+                    .entry(normalize(&subject.desc))
+                    .or_insert((subject.clone(), 0))
+                    .1 += 1;
+            }
+        }
+
+        let mut timetable = Timetable::default();
+        for ((weekday, position), subjects) in counts {
+            // ties are broken by name, so that the result does not change between runs
+            if let Some((_, (subject, _))) = subjects
+                .into_iter()
+                .max_by(|a, b| a.1 .1.cmp(&b.1 .1).then(b.0.cmp(&a.0)))
+            {
+                timetable
+                    .days
+                    .entry(weekday)
+                    .or_default()
+                    .insert(position, subject);
+            }
+        }
+
+        timetable
+    }
+
+    // load the timetable stored by the last update, None if it has never been stored
+    pub fn load() -> Option<Self> {
+        let file = std::fs::File::open(USER_CONFIG.data_path(TIMETABLE_FILE)).ok()?;
+        serde_json::from_reader(file).ok()
+    }
+
+    // written to a temporary file first, so that an interrupted write leaves no broken timetable
+    pub fn store(&self) {
+        let path = USER_CONFIG.data_path(TIMETABLE_FILE);
+        let temp_path = path.with_extension("json.tmp");
+        let file = match std::fs::File::create(&temp_path) {
+            Ok(v) => v,
+            Err(e) => panic!("error at creating the timetable file: {}", e),
+        };
+        match serde_json::to_writer(file, self) {
+            Ok(_) => (),
+            Err(e) => panic!("error at writing the timetable file: {}", e),
+        };
+        if let Err(e) = std::fs::rename(&temp_path, &path) {
+            panic!("error at replacing the timetable file: {}", e);
+        }
+    }
+
+    // the subjects set in config.toml replace the inferred ones
+    pub fn with_overrides(mut self) -> Self {
+        let overrides = &USER_CONFIG.timetable_settings.overrides;
+        for (weekday_index, weekday) in WEEKDAYS.iter().enumerate() {
+            let day = match overrides.get(weekday_key(*weekday)) {
+                Some(v) => v,
+                None => continue,
+            };
+            for (position, subject) in day {
+                let position: u32 = match position.parse() {
+                    Ok(v) => v,
+                    Err(_) => panic!(
+                        "error at parsing timetable overrides: {} is not an hour position",
+                        position
+                    ),
+                };
+                self.days
+                    .entry(weekday_index as u32)
+                    .or_default()
+                    .insert(position, Subject::new(None, None, subject));
+            }
+        }
+        self
+    }
+
+    pub fn subjects_of(&self, weekday: Weekday) -> Option<&BTreeMap<u32, Subject>> {
+        self.days
+            .get(&weekday.num_days_from_monday())
+            .filter(|x| !x.is_empty())
+    }
+}
+
+// start time of an hour position following config.toml
+pub fn hour_start(position: u32) -> Option<NaiveTime> {
+    let hours = &USER_CONFIG.timetable_settings.hours;
+    let start = hours.get((position as usize).checked_sub(1)?)?;
+    match NaiveTime::parse_from_str(start, "%H:%M") {
+        Ok(v) => Some(v),
+        Err(_) => panic!("error at parsing timetable hours: {} is not HH:MM", start),
+    }
+}
+
+// range of days used to infer the timetable, from N weeks ago to today
+pub fn inference_range(today: NaiveDate) -> (NaiveDate, NaiveDate) {
+    let weeks = USER_CONFIG.timetable_settings.weeks.max(1) as i64;
+    (today - Duration::weeks(weeks), today)
+}
+
+pub struct Slot {
+    pub start: NaiveDateTime,
+    pub position: u32,
+    pub subject: String,
+}
+
+// the lesson taking place at a given time
+pub fn current_slot(timetable: &Timetable, now: NaiveDateTime) -> Option<Slot> {
+    let hour_length = Duration::minutes(USER_CONFIG.timetable_settings.hour_length as i64);
+    let day = timetable.subjects_of(now.weekday())?;
+    day.iter().find_map(|(position, subject)| {
+        let start = now.date().and_time(hour_start(*position)?);
+        if start <= now && now < start + hour_length {
+            Some(Slot {
+                start,
+                position: *position,
+                subject: subject.name(),
+            })
+        } else {
+            None
+        }
+    })
+}
+
+// the first lesson starting after a given time, looking at the following week
+pub fn next_slot(timetable: &Timetable, now: NaiveDateTime) -> Option<Slot> {
+    for offset in 0..8 {
+        let date = now.date() + Duration::days(offset);
+        let day = match timetable.subjects_of(date.weekday()) {
+            Some(v) => v,
+            None => continue,
+        };
+        let slot = day.iter().find_map(|(position, subject)| {
+            let start = date.and_time(hour_start(*position)?);
+            if start > now {
+                Some(Slot {
+                    start,
+                    position: *position,
+                    subject: subject.name(),
+                })
+            } else {
+                None
+            }
+        });
+        if slot.is_some() {
+            return slot;
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::response_types::fixtures::lesson;
+
+    fn names(timetable: &Timetable, weekday: Weekday) -> Vec<(u32, String)> {
+        timetable
+            .subjects_of(weekday)
+            .map(|day| day.iter().map(|(k, v)| (*k, v.name())).collect())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn from_lessons_takes_the_most_frequent_subject() {
+        let mut lab = lesson("2025-10-07", 1, Some((3, "LABORATORIO")), "Sensori");
+        lab.evtDuration = 2;
+        let mut forces = lesson("2025-10-27", 1, Some((2, "FISICA")), "Forze");
+        forces.subjectId = None;
+        let timetable = Timetable::from_lessons(&[
+            // mondays, FISICA twice in the first hour, once sent without its id
+            lesson("2025-10-06", 1, Some((2, "FISICA")), "Moto"),
+            lesson("2025-10-13", 1, Some((1, "MATEMATICA")), "Derivate"),
+            lesson("2025-10-20", 1, None, "Assemblea"),
+            forces,
+            // a tie is broken by name
+            lesson("2025-10-06", 2, Some((2, "FISICA")), "Moto"),
+            lesson("2025-10-13", 2, Some((4, "CHIMICA")), "Atomi"),
+            // tuesday, a lesson of two hours
+            lab,
+        ]);
+
+        assert_eq!(
+            names(&timetable, Weekday::Mon),
+            vec![(1, "FISICA".to_string()), (2, "CHIMICA".to_string())]
+        );
+        assert_eq!(
+            names(&timetable, Weekday::Tue),
+            vec![
+                (1, "LABORATORIO".to_string()),
+                (2, "LABORATORIO".to_string())
+            ]
+        );
+        assert!(timetable.subjects_of(Weekday::Wed).is_none());
+    }
+
+    #[test]
+    fn slots_follow_the_hours_of_config() {
+        let timetable = Timetable::from_lessons(&[
+            lesson("2025-10-06", 1, Some((2, "FISICA")), "Moto"),
+            lesson("2025-10-06", 3, Some((1, "MATEMATICA")), "Derivate"),
+            lesson("2025-10-10", 2, Some((4, "CHIMICA")), "Atomi"),
+        ]);
+        let at = |day, hour, minute| {
+            NaiveDate::from_ymd_opt(2025, 10, day)
+                .unwrap()
+                .and_hms_opt(hour, minute, 0)
+                .unwrap()
+        };
+
+        let slot = current_slot(&timetable, at(13, 8, 30)).unwrap();
+        assert_eq!((slot.position, slot.subject.as_str()), (1, "FISICA"));
+        // no lesson in the second hour of monday
+        assert!(current_slot(&timetable, at(13, 9, 0)).is_none());
+
+        // the next one skips the empty hour
+        let slot = next_slot(&timetable, at(13, 8, 30)).unwrap();
+        assert_eq!(
+            (slot.start, slot.subject.as_str()),
+            (at(13, 10, 0), "MATEMATICA")
+        );
+
+        // after the last lesson of friday, the next one is on monday
+        let slot = next_slot(&timetable, at(17, 9, 0)).unwrap();
+        assert_eq!(
+            (slot.start, slot.subject.as_str()),
+            (at(20, 8, 0), "FISICA")
+        );
+
+        assert!(next_slot(&Timetable::default(), at(13, 8, 0)).is_none());
+    }
+
+    #[test]
+    fn stored_subjects_keep_the_name_of_the_server() {
+        let timetable = Timetable::from_lessons(&[lesson(
+            "2025-10-06",
+            1,
+            Some((5, "LINGUA E LETTERATURA ITALIANA")),
+            "Dante",
+        )]);
+        let json = serde_json::to_string(&timetable).unwrap();
+        assert!(json.contains("LINGUA E LETTERATURA ITALIANA"));
+
+        // the alias of config.example.toml is applied when displayed
+        let timetable: Timetable = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            names(&timetable, Weekday::Mon),
+            vec![(1, "ITALIANO".to_string())]
+        );
+    }
+}
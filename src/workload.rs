@@ -0,0 +1,121 @@
+// Module: workload
+use crate::assessments::detect;
+use crate::homework::{collect, HomeworkState};
+use crate::response_types::{Agenda, Lesson};
+use crate::subjects::Subject;
+use chrono::NaiveDate;
+use std::collections::HashSet;
+
+// weeks displayed by the workload command when --weeks is not given
+pub const DEFAULT_WEEKS: u32 = 4;
+
+// symbols of the heatmap, from a free day to an overloaded one
+const HEAT_LEVELS: [&str; 5] = ["·", "░", "▒", "▓", "█"];
+
+// a homework or a test due on a day
+pub struct Load {
+    pub date: NaiveDate,
+    pub subject: Option<Subject>,
+    pub test: bool,
+}
+
+/*
+Collect the homework to do and the tests between two dates, both included
+the homework marked as done is not counted, and a homework announcing a test only counts as a test
+*/
+pub fn workload(
+    agenda: &[Agenda],
+    lessons: &[Lesson],
+    state: &HomeworkState,
+    (start, end): (NaiveDate, NaiveDate),
+) -> Vec<Load> {
+    let in_range = |date: NaiveDate| date >= start && date <= end;
+
+    let tests = detect(agenda, lessons);
+    let test_ids: HashSet<u32> = tests.iter().filter_map(|x| x.id).collect();
+
+    let homework = collect(agenda, lessons, state)
+        .into_iter()
+        .filter(|x| !x.done && in_range(x.due) && !test_ids.contains(&x.id))
+        .map(|x| Load {
+            date: x.due,
+            subject: x.subject,
+            test: false,
+        });
+    let tests = tests
+        .into_iter()
+        .filter(|x| in_range(x.date))
+        .map(|x| Load {
+            date: x.date,
+            subject: x.subject,
+            test: true,
+        });
+
+    homework.chain(tests).collect()
+}
+
+// symbol of the heatmap for an amount of homework and tests
+pub fn heat(count: usize) -> &'static str {
+    HEAT_LEVELS[count.min(HEAT_LEVELS.len() - 1)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::response_types::fixtures::{agenda, lesson};
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn heat_saturates_at_the_last_level() {
+        let levels: Vec<&str> = (0..7).map(heat).collect();
+        assert_eq!(levels, vec!["·", "░", "▒", "▓", "█", "█", "█"]);
+    }
+
+    #[test]
+    fn workload_counts_every_load_once() {
+        let events = vec![
+            agenda(1, "AGHW", "2025-10-07T08:00:00+02:00", "Esercizi pag. 12"),
+            // a homework announcing a test
+            agenda(
+                2,
+                "AGHW",
+                "2025-10-08T08:00:00+02:00",
+                "Studiare per la verifica",
+            ),
+            agenda(
+                3,
+                "AGHW",
+                "2025-10-09T08:00:00+02:00",
+                "Leggere il capitolo 2",
+            ),
+            agenda(4, "AGNT", "2025-10-10T08:00:00+02:00", "Interrogazione"),
+            // after the range
+            agenda(5, "AGHW", "2025-10-13T08:00:00+02:00", "Ripasso"),
+        ];
+        let lessons = vec![lesson(
+            "2025-10-06",
+            1,
+            Some((2, "FISICA")),
+            "Test sul moto",
+        )];
+        let state = HomeworkState {
+            done: BTreeSet::from([3]),
+        };
+        let date = |day| NaiveDate::from_ymd_opt(2025, 10, day).unwrap();
+
+        let loads = workload(&events, &lessons, &state, (date(6), date(12)));
+        let found: Vec<(NaiveDate, bool)> = loads.iter().map(|x| (x.date, x.test)).collect();
+        assert_eq!(
+            found,
+            vec![
+                (date(7), false),
+                (date(6), true),
+                (date(8), true),
+                (date(10), true),
+            ]
+        );
+        assert!(loads
+            .iter()
+            .all(|x| x.subject.as_ref().is_some_and(|x| x.desc == "FISICA")));
+    }
+}